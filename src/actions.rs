@@ -0,0 +1,137 @@
+use anyhow::anyhow;
+use std::env;
+use std::fs::OpenOptions;
+use std::io::Write;
+use tracing::warn;
+
+use crate::models::release::ReleaseSummary;
+use crate::models::target::Target;
+
+pub fn is_github_actions() -> bool {
+    env::var("GITHUB_ACTIONS").is_ok_and(|value| value == "true")
+}
+
+// Workflow commands end at the first newline, so it has to be escaped
+// https://docs.github.com/en/actions/using-workflows/workflow-commands-for-github-actions
+fn escape_data(data: &str) -> String {
+    data.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+fn escape_property(property: &str) -> String {
+    escape_data(property)
+        .replace(':', "%3A")
+        .replace(',', "%2C")
+}
+
+// Written to stderr like logs, the runner reads workflow commands from both
+// streams and stdout is kept for the command's output
+pub fn annotate(level: &str, file: Option<&str>, line: Option<usize>, message: &str) {
+    let mut properties = vec![];
+
+    if let Some(file) = file {
+        properties.push(format!("file={}", escape_property(file)))
+    }
+
+    if let Some(line) = line {
+        properties.push(format!("line={}", line))
+    }
+
+    let properties = if properties.is_empty() {
+        String::new()
+    } else {
+        format!(" {}", properties.join(","))
+    };
+
+    eprintln!("::{}{}::{}", level, properties, escape_data(message))
+}
+
+pub fn print_warning(message: &str) {
+    if is_github_actions() {
+        annotate("warning", None, None, message)
+    } else {
+        warn!("{}", message)
+    }
+}
+
+// The error is still printed by `main` as usual, this only makes it show
+// up as an annotation on the workflow run.
+pub fn annotate_error(message: &str) {
+    if is_github_actions() {
+        annotate("error", None, None, message)
+    }
+}
+
+fn append_to_env_file(env_var: &str, contents: &str) -> Result<(), anyhow::Error> {
+    let path = match env::var(env_var) {
+        Ok(path) => path,
+        Err(err) => return Err(anyhow!("Failed to get `{}`: {}", env_var, err)),
+    };
+
+    let mut file = match OpenOptions::new().create(true).append(true).open(&path) {
+        Ok(file) => file,
+        Err(err) => return Err(anyhow!("Failed to open `{}`: {}", env_var, err)),
+    };
+
+    match file.write_all(contents.as_bytes()) {
+        Ok(_) => Ok(()),
+        Err(err) => Err(anyhow!("Failed to write to `{}`: {}", env_var, err)),
+    }
+}
+
+pub fn write_outputs(summary: &ReleaseSummary) -> Result<(), anyhow::Error> {
+    let mut outputs = vec![
+        ("version_name", Some(summary.version_name.clone())),
+        ("version_number", Some(summary.version_number.clone())),
+    ];
+
+    for result in &summary.results {
+        let (id_name, url_name) = match result.target {
+            Target::Github => ("github_release_id", "github_release_url"),
+            Target::Modrinth => ("modrinth_version_id", "modrinth_version_url"),
+            Target::Discord => ("discord_message_id", "discord_message_url"),
+        };
+
+        outputs.push((id_name, result.id.clone()));
+        outputs.push((url_name, result.url.clone()));
+    }
+
+    let mut contents = String::new();
+
+    for (name, value) in outputs {
+        if let Some(value) = value {
+            contents.push_str(&format!("{}={}\n", name, value.replace('\n', " ")))
+        }
+    }
+
+    append_to_env_file("GITHUB_OUTPUT", &contents)
+}
+
+pub fn write_step_summary(summary: &ReleaseSummary) -> Result<(), anyhow::Error> {
+    let mut contents = format!("### Released {}\n\n", summary.version_name);
+
+    contents.push_str("| Target | Id | Link |\n| --- | --- | --- |\n");
+
+    for result in &summary.results {
+        contents.push_str(&format!(
+            "| {} | {} | {} |\n",
+            result.target.formatted(),
+            result.id.as_deref().unwrap_or("-"),
+            match &result.url {
+                Some(url) => format!("[{}]({})", url, url),
+                None => "-".to_owned(),
+            }
+        ))
+    }
+
+    contents.push_str("\n| File | SHA-512 |\n| --- | --- |\n");
+
+    for file in &summary.files {
+        contents.push_str(&format!("| {} | `{}` |\n", file.file_name, file.sha512))
+    }
+
+    contents.push('\n');
+
+    append_to_env_file("GITHUB_STEP_SUMMARY", &contents)
+}
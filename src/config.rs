@@ -0,0 +1,456 @@
+use anyhow::anyhow;
+use serde::de::DeserializeOwned;
+use std::path::{Path, PathBuf};
+use std::{env, fs};
+use toml_edit::visit_mut::VisitMut;
+use toml_edit::{Document, Formatted, TableLike};
+
+use crate::actions::print_warning;
+use crate::error::PeonyError;
+
+pub const MOD_CONFIG_FILE: &str = "peony_mod.toml";
+pub const MODPACK_CONFIG_FILE: &str = "mrpack.toml";
+
+// Bump this whenever a config change would break existing files, and add a
+// migration for it to `CONFIG_MIGRATIONS`.
+pub const CONFIG_FORMAT_VERSION: i32 = 1;
+
+// Each migration upgrades a config from the version it's listed with to the
+// next one.
+type ConfigMigration = fn(&mut Document) -> Result<(), anyhow::Error>;
+
+const CONFIG_MIGRATIONS: &[(i32, ConfigMigration)] = &[];
+
+pub fn read_config_string(path: &Path) -> Result<String, anyhow::Error> {
+    if !path.exists() {
+        return Err(PeonyError::Config(format!("Failed to find `{}` file", path.display())).into());
+    }
+
+    match fs::read_to_string(path) {
+        Ok(content_string) => Ok(content_string),
+        Err(err) => Err(PeonyError::Config(format!("Failed to read config file: {}", err)).into()),
+    }
+}
+
+pub fn load_config<T: DeserializeOwned>(path: &Path) -> Result<T, anyhow::Error> {
+    let mut document = read_config_document(path)?;
+
+    let old_version = migrate_config(&mut document)?;
+
+    if old_version < CONFIG_FORMAT_VERSION {
+        print_warning(&format!(
+            "`{}` uses config format version {}, run `peony migrate-config` to update it to \
+             version {}",
+            path.display(),
+            old_version,
+            CONFIG_FORMAT_VERSION
+        ))
+    }
+
+    interpolate_config(&mut document)?;
+
+    match toml::from_str(&document.to_string()) {
+        Ok(config) => Ok(config),
+        Err(err) => Err(PeonyError::Config(format!("Failed to parse config file: {}", err)).into()),
+    }
+}
+
+struct EnvInterpolator {
+    errors: Vec<String>,
+}
+
+impl VisitMut for EnvInterpolator {
+    fn visit_string_mut(&mut self, node: &mut Formatted<String>) {
+        match interpolate_env(node.value()) {
+            Ok(value) if value != *node.value() => {
+                let decor = node.decor().clone();
+                *node = Formatted::new(value);
+                *node.decor_mut() = decor;
+            }
+            Ok(_) => (),
+            Err(err) => self.errors.push(err.to_string()),
+        }
+    }
+}
+
+// Expands environment variables in every string value of the config. Keys
+// are left alone.
+pub fn interpolate_config(document: &mut Document) -> Result<(), anyhow::Error> {
+    let mut interpolator = EnvInterpolator { errors: vec![] };
+    interpolator.visit_document_mut(document);
+
+    if interpolator.errors.is_empty() {
+        Ok(())
+    } else {
+        Err(PeonyError::Config(format!(
+            "Failed to interpolate config file:\n{}",
+            interpolator.errors.join("\n")
+        ))
+        .into())
+    }
+}
+
+// Expands `${VAR}` and `${VAR:-default}`, where the default is also used
+// when the variable is set but empty. `$${` escapes a literal `${`.
+pub fn interpolate_env(value: &str) -> Result<String, anyhow::Error> {
+    let mut result = String::new();
+    let mut rest = value;
+
+    while let Some(start) = rest.find('$') {
+        result.push_str(&rest[..start]);
+        let after_dollar = &rest[start + 1..];
+
+        if let Some(after_escape) = after_dollar.strip_prefix("${") {
+            result.push_str("${");
+            rest = after_escape;
+            continue;
+        }
+
+        let expression = match after_dollar.strip_prefix('{') {
+            Some(expression) => expression,
+            None => {
+                result.push('$');
+                rest = after_dollar;
+                continue;
+            }
+        };
+
+        let end = match expression.find('}') {
+            Some(end) => end,
+            None => return Err(PeonyError::Config(format!("Unclosed `${{` in `{}`", value)).into()),
+        };
+
+        // The first `}` closes the expression, so a nested one would leave
+        // part of it behind
+        if expression[..end].contains("${") {
+            return Err(
+                PeonyError::Config(format!("Nested `${{` isn't supported in `{}`", value)).into(),
+            );
+        }
+
+        let (name, default) = match expression[..end].split_once(":-") {
+            Some((name, default)) => (name, Some(default)),
+            None => (&expression[..end], None),
+        };
+
+        if name.is_empty() {
+            return Err(PeonyError::Config(format!("Empty variable name in `{}`", value)).into());
+        }
+
+        match (env::var(name), default) {
+            (Ok(var), Some(default)) if var.is_empty() => result.push_str(default),
+            (Ok(var), _) => result.push_str(&var),
+            (Err(_), Some(default)) => result.push_str(default),
+            (Err(err), None) => {
+                return Err(PeonyError::Config(format!("Failed to get `{}`: {}", name, err)).into())
+            }
+        }
+
+        rest = &expression[end + 1..];
+    }
+
+    result.push_str(rest);
+
+    Ok(result)
+}
+
+pub fn parse_config_document(content: &str) -> Result<Document, anyhow::Error> {
+    match content.parse::<Document>() {
+        Ok(document) => Ok(document),
+        Err(err) => Err(PeonyError::Config(format!("Failed to parse config file: {}", err)).into()),
+    }
+}
+
+// Reads the config with everything it `extends` merged in
+pub fn read_config_document(path: &Path) -> Result<Document, anyhow::Error> {
+    resolve_extends(path, &mut vec![])
+}
+
+fn resolve_extends(path: &Path, seen_paths: &mut Vec<PathBuf>) -> Result<Document, anyhow::Error> {
+    let mut document = parse_config_document(&read_config_string(path)?)?;
+
+    let canonical_path = match path.canonicalize() {
+        Ok(path) => path,
+        Err(err) => {
+            return Err(PeonyError::Config(format!(
+                "Failed to resolve `{}`: {}",
+                path.display(),
+                err
+            ))
+            .into())
+        }
+    };
+
+    if seen_paths.contains(&canonical_path) {
+        return Err(PeonyError::Config(format!(
+            "Found an `extends` cycle at `{}`",
+            path.display()
+        ))
+        .into());
+    }
+
+    seen_paths.push(canonical_path);
+
+    let base_path = match document.remove("extends") {
+        Some(item) => match item.as_str() {
+            // Relative to the config that extends it, not the working directory
+            Some(base_path) => path.parent().unwrap_or(Path::new(".")).join(base_path),
+            None => {
+                return Err(PeonyError::Config(format!(
+                    "`extends` in `{}` must be a path",
+                    path.display()
+                ))
+                .into())
+            }
+        },
+        None => return Ok(document),
+    };
+
+    let mut base_document = resolve_extends(&base_path, seen_paths)?;
+    merge_tables(base_document.as_table_mut(), document.as_table());
+
+    Ok(base_document)
+}
+
+// Tables are merged key by key, anything else (including arrays) replaces
+// the base value.
+fn merge_tables(base: &mut dyn TableLike, overlay: &dyn TableLike) {
+    for (key, item) in overlay.iter() {
+        match (
+            base.get_mut(key)
+                .and_then(|base_item| base_item.as_table_like_mut()),
+            item.as_table_like(),
+        ) {
+            (Some(base_table), Some(overlay_table)) => merge_tables(base_table, overlay_table),
+            _ => {
+                base.insert(key, item.clone());
+            }
+        }
+    }
+}
+
+pub fn config_format_version(document: &Document) -> Result<i32, anyhow::Error> {
+    match document
+        .get("config_format_version")
+        .and_then(|item| item.as_integer())
+    {
+        Some(version) => Ok(version as i32),
+        None => Err(PeonyError::Config(
+            "Failed to get `config_format_version` from config file".to_owned(),
+        )
+        .into()),
+    }
+}
+
+pub fn check_config_format_version(version: i32) -> Result<(), anyhow::Error> {
+    if version > CONFIG_FORMAT_VERSION {
+        return Err(PeonyError::Config(format!(
+            "Config format version {} is newer than the latest version this Peony supports ({}), \
+             update Peony to use this config",
+            version, CONFIG_FORMAT_VERSION
+        ))
+        .into());
+    }
+
+    Ok(())
+}
+
+// Upgrades the config to the current format version, returning the version
+// it had before.
+pub fn migrate_config(document: &mut Document) -> Result<i32, anyhow::Error> {
+    let old_version = config_format_version(document)?;
+    check_config_format_version(old_version)?;
+
+    if old_version == CONFIG_FORMAT_VERSION {
+        return Ok(old_version);
+    }
+
+    for (from_version, migration) in CONFIG_MIGRATIONS {
+        if *from_version >= old_version {
+            migration(document)?
+        }
+    }
+
+    // Keep any comment on the version line
+    if let Some(value) = document
+        .get_mut("config_format_version")
+        .and_then(|item| item.as_value_mut())
+    {
+        let decor = value.decor().clone();
+        *value = toml_edit::Value::from(CONFIG_FORMAT_VERSION as i64);
+        *value.decor_mut() = decor;
+    }
+
+    Ok(old_version)
+}
+
+pub fn migrate_config_file(path: &Path) -> Result<(), anyhow::Error> {
+    let content_string = read_config_string(path)?;
+    let mut document = parse_config_document(&content_string)?;
+
+    let old_version = migrate_config(&mut document)?;
+
+    if old_version == CONFIG_FORMAT_VERSION {
+        println!("`{}` is already up to date", path.display());
+        return Ok(());
+    }
+
+    match fs::write(path, document.to_string()) {
+        Ok(_) => println!(
+            "Migrated `{}` from config format version {} to {}!",
+            path.display(),
+            old_version,
+            CONFIG_FORMAT_VERSION
+        ),
+        Err(err) => return Err(anyhow!("Failed to write `{}`: {}", path.display(), err)),
+    }
+
+    Ok(())
+}
+
+// Migrates the given config, or every config in the current directory
+pub fn migrate_project_configs(config_path: Option<PathBuf>) -> Result<(), anyhow::Error> {
+    let config_paths: Vec<PathBuf> = match config_path {
+        Some(path) => vec![path],
+        None => [MOD_CONFIG_FILE, MODPACK_CONFIG_FILE]
+            .iter()
+            .map(PathBuf::from)
+            .filter(|path| path.exists())
+            .collect(),
+    };
+
+    if config_paths.is_empty() {
+        return Err(PeonyError::Config(format!(
+            "Failed to find `{}` or `{}` file",
+            MOD_CONFIG_FILE, MODPACK_CONFIG_FILE
+        ))
+        .into());
+    }
+
+    for path in config_paths {
+        migrate_config_file(&path)?
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("peony-config-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn interpolates_variables() {
+        env::set_var("PEONY_TEST_SET", "value");
+        env::set_var("PEONY_TEST_EMPTY", "");
+        env::remove_var("PEONY_TEST_UNSET");
+
+        assert_eq!(
+            interpolate_env("a ${PEONY_TEST_SET} b").unwrap(),
+            "a value b"
+        );
+        assert_eq!(interpolate_env("${PEONY_TEST_SET:-x}").unwrap(), "value");
+        assert_eq!(interpolate_env("${PEONY_TEST_EMPTY:-x}").unwrap(), "x");
+        assert_eq!(interpolate_env("${PEONY_TEST_EMPTY}").unwrap(), "");
+        assert_eq!(interpolate_env("${PEONY_TEST_UNSET:-x}").unwrap(), "x");
+        assert_eq!(interpolate_env("${PEONY_TEST_UNSET:-}").unwrap(), "");
+        assert_eq!(interpolate_env("$5 and $").unwrap(), "$5 and $");
+    }
+
+    #[test]
+    fn escapes_interpolation() {
+        assert_eq!(
+            interpolate_env("$${PEONY_TEST_UNSET}").unwrap(),
+            "${PEONY_TEST_UNSET}"
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_interpolation() {
+        env::remove_var("PEONY_TEST_MISSING");
+
+        assert!(interpolate_env("${PEONY_TEST_MISSING}").is_err());
+        assert!(interpolate_env("${PEONY_TEST_SET").is_err());
+        assert!(interpolate_env("${}").is_err());
+        assert!(interpolate_env("${:-default}").is_err());
+        assert!(interpolate_env("${PEONY_TEST_MISSING:-${PEONY_TEST_SET}}").is_err());
+    }
+
+    #[test]
+    fn merges_tables_and_replaces_arrays() {
+        let mut base = parse_config_document(
+            r#"
+loaders = ["fabric", "quilt"]
+
+[github]
+repo_owner = "base-owner"
+repo_name = "base-name"
+"#,
+        )
+        .unwrap();
+        let overlay = parse_config_document(
+            r#"
+loaders = ["neoforge"]
+
+[github]
+repo_name = "overlay-name"
+"#,
+        )
+        .unwrap();
+
+        merge_tables(base.as_table_mut(), overlay.as_table());
+
+        let merged: toml::Table = toml::from_str(&base.to_string()).unwrap();
+
+        assert_eq!(merged["github"]["repo_owner"].as_str(), Some("base-owner"));
+        assert_eq!(merged["github"]["repo_name"].as_str(), Some("overlay-name"));
+        assert_eq!(
+            merged["loaders"].as_array().unwrap(),
+            &vec![toml::Value::from("neoforge")]
+        );
+    }
+
+    #[test]
+    fn resolves_extends_relative_to_config() {
+        let dir = test_dir("extends");
+        fs::create_dir_all(dir.join("packs").join("a")).unwrap();
+        fs::write(
+            dir.join("base.toml"),
+            "version_name_format = \"base\"\nmc_version_alias = \"1.20\"\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("packs").join("a").join("mrpack.toml"),
+            "extends = \"../../base.toml\"\nversion_name_format = \"a\"\n",
+        )
+        .unwrap();
+
+        let document =
+            read_config_document(&dir.join("packs").join("a").join("mrpack.toml")).unwrap();
+
+        assert!(document.get("extends").is_none());
+        assert_eq!(document["version_name_format"].as_str(), Some("a"));
+        assert_eq!(document["mc_version_alias"].as_str(), Some("1.20"));
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn rejects_extends_cycles() {
+        let dir = test_dir("cycle");
+        fs::write(dir.join("a.toml"), "extends = \"b.toml\"\n").unwrap();
+        fs::write(dir.join("b.toml"), "extends = \"a.toml\"\n").unwrap();
+
+        let err = read_config_document(&dir.join("a.toml")).unwrap_err();
+
+        assert!(err.to_string().contains("cycle"));
+
+        let _ = fs::remove_dir_all(dir);
+    }
+}
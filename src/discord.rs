@@ -1,52 +1,104 @@
-use crate::models::modrinth::project::ProjectResponse;
+use crate::actions::print_warning;
+use crate::dry_run::{dry_run_request, DryRunRequest};
+use crate::error::PeonyError;
+use crate::models::modrinth::project::{ProjectResponse, ProjectType};
 use crate::models::modrinth::ModrinthUrl;
+use crate::models::release::{FileType, PublishResult, Release};
+use crate::models::target::Target;
 use crate::models::{DiscordConfig, GithubConfig};
+use crate::modrinth::get_modrinth_token;
+use crate::publisher::Publisher;
+use crate::util::send_request;
 use anyhow::anyhow;
+use async_trait::async_trait;
 use chrono::Utc;
+use serde_json::{json, Value};
 use serenity::model::channel::Embed;
+use serenity::model::id::MessageId;
 use serenity::model::webhook::Webhook;
 use std::env;
+use tracing::info;
 
-pub async fn send_discord_webhook(
-    discord_config: &DiscordConfig,
-    modrinth_url: &ModrinthUrl,
-    modrinth_project_id: &String,
-    github_config: &GithubConfig,
-    version_name: &String,
-    changelog: &String,
-) -> Result<(), anyhow::Error> {
-    let modrinth_token = match env::var("MODRINTH_TOKEN") {
-        Ok(token) => token,
+pub struct DiscordPublisher {
+    pub config: DiscordConfig,
+    pub github_config: GithubConfig,
+    pub modrinth_project_id: String,
+    pub modrinth_url: ModrinthUrl,
+}
+
+pub fn get_webhook_url() -> Result<String, anyhow::Error> {
+    match env::var("WEBHOOK_URL") {
+        Ok(url) => Ok(url),
+        Err(err) => Err(PeonyError::Auth(format!("Failed to get webhook url: {}", err)).into()),
+    }
+}
+
+async fn get_webhook(http: &serenity::http::Http, url: &str) -> Result<Webhook, anyhow::Error> {
+    match Webhook::from_url(http, url).await {
+        Ok(webhook) => Ok(webhook),
         Err(err) => {
-            return Err(anyhow!(
-                "Failed to get `MODRINTH_TOKEN` from environment: {}",
-                err
-            ))
+            Err(PeonyError::Network(format!("Failed to get Discord webhook: {}", err)).into())
         }
-    };
+    }
+}
 
-    let modrinth_project = match reqwest::Client::new()
-        .get(format!(
-            "{}/project/{}",
-            modrinth_url.labrinth, modrinth_project_id
-        ))
-        .header("Authorization", modrinth_token)
-        .send()
-        .await
+// The parts of the Modrinth project the embed shows
+pub struct EmbedProject {
+    pub slug: String,
+    pub color: Option<i32>,
+    pub project_type: ProjectType,
+}
+
+impl From<ProjectResponse> for EmbedProject {
+    fn from(project: ProjectResponse) -> Self {
+        Self {
+            slug: project.slug,
+            color: project.color,
+            project_type: project.project_type,
+        }
+    }
+}
+
+pub async fn get_modrinth_project(
+    modrinth_url: &ModrinthUrl,
+    modrinth_project_id: &str,
+) -> Result<ProjectResponse, anyhow::Error> {
+    let modrinth_token = get_modrinth_token()?;
+
+    match send_request(
+        reqwest::Client::new()
+            .get(format!(
+                "{}/project/{}",
+                modrinth_url.labrinth, modrinth_project_id
+            ))
+            .header("Authorization", modrinth_token),
+    )
+    .await
     {
         Ok(res) => match res.json::<ProjectResponse>().await {
-            Ok(json) => json,
-            Err(err) => {
-                return Err(anyhow!(
-                    "Error parsing response from get project: {}\n\
-                     Make sure your Modrinth token can read projects!",
-                    err.to_string()
-                ))
-            }
+            Ok(json) => Ok(json),
+            Err(err) => Err(anyhow!(
+                "Error parsing response from get project: {}\n\
+                 Make sure your Modrinth token can read projects!",
+                err.to_string()
+            )),
         },
-        Err(err) => return Err(anyhow!("Error getting project from project id: {}", err)),
-    };
+        Err(err) => Err(PeonyError::Network(format!(
+            "Error getting project from project id: {}",
+            err
+        ))
+        .into()),
+    }
+}
 
+pub fn create_embed(
+    discord_config: &DiscordConfig,
+    modrinth_url: &ModrinthUrl,
+    modrinth_project: &EmbedProject,
+    github_config: &GithubConfig,
+    version_name: &str,
+    changelog: &str,
+) -> Value {
     let description = format!(
         "\
         **New release!**\n\n\
@@ -65,18 +117,15 @@ pub async fn send_discord_webhook(
 
     let embed_color = match discord_config.embed_color {
         Some(color) => color,
-        None => match discord_config.embed_color {
-            Some(color) => color,
-            None => match modrinth_project.color {
-                Some(color) => color as u32,
-                None => 0x232634,
-            },
+        None => match modrinth_project.color {
+            Some(color) => color as u32,
+            None => 0x232634,
         },
     } as i32;
 
     let release_time = Utc::now().format("%b, %d %Y %r");
 
-    let embed = Embed::fake(|mut e| {
+    Embed::fake(|mut e| {
         if let Some(url) = &discord_config.embed_image_url {
             e = e.image(url)
         }
@@ -95,24 +144,137 @@ pub async fn send_discord_webhook(
                     release_time
                 ))
             })
-    });
-
-    let http = serenity::http::Http::new("token");
-    let url = match env::var("WEBHOOK_URL") {
-        Ok(url) => url,
-        Err(err) => return Err(anyhow!("Failed to get webhook url: {}", err)),
-    };
-
-    let webhook = Webhook::from_url(&http, &url).await?;
-
-    match webhook
-        .execute(&http, true, |w| {
-            w.content(&discord_config.discord_ping_role)
-                .embeds(vec![embed])
-        })
-        .await
-    {
-        Ok(_) => Ok(()),
-        Err(err) => Err(anyhow!("Failed to send Discord webhook: {}", err)),
+    })
+}
+
+impl DiscordPublisher {
+    // A dry run doesn't need a token or network access, so it falls back to
+    // the project id, which Modrinth links accept too
+    async fn create_embed(&self, release: &Release, dry_run: bool) -> Result<Value, anyhow::Error> {
+        let modrinth_project =
+            match get_modrinth_project(&self.modrinth_url, &self.modrinth_project_id).await {
+                Ok(project) => EmbedProject::from(project),
+                Err(err) if dry_run => {
+                    print_warning(&format!(
+                        "Failed to get Modrinth project, using placeholders in the Discord \
+                         embed: {:#}",
+                        err
+                    ));
+
+                    EmbedProject {
+                        slug: self.modrinth_project_id.clone(),
+                        color: None,
+                        project_type: match release.primary_file() {
+                            Some(file) if file.file_type == FileType::Modpack => {
+                                ProjectType::Modpack
+                            }
+                            _ => ProjectType::Mod,
+                        },
+                    }
+                }
+                Err(err) => return Err(err),
+            };
+
+        Ok(create_embed(
+            &self.config,
+            &self.modrinth_url,
+            &modrinth_project,
+            &self.github_config,
+            &release.version_name,
+            &release.changelog,
+        ))
+    }
+}
+
+#[async_trait]
+impl Publisher for DiscordPublisher {
+    fn target(&self) -> Target {
+        Target::Discord
+    }
+
+    async fn preflight(&self, _version_number: Option<&str>) -> Result<(), anyhow::Error> {
+        get_webhook_url()?;
+        get_modrinth_token()?;
+
+        Ok(())
+    }
+
+    async fn publish(
+        &self,
+        release: &Release,
+        result: &mut PublishResult,
+    ) -> Result<(), anyhow::Error> {
+        let embed = self.create_embed(release, false).await?;
+
+        let http = serenity::http::Http::new("token");
+        let url = get_webhook_url()?;
+
+        let webhook = get_webhook(&http, &url).await?;
+
+        match webhook
+            .execute(&http, true, |w| {
+                w.content(&self.config.discord_ping_role)
+                    .embeds(vec![embed])
+            })
+            .await
+        {
+            Ok(message) => {
+                if let Some(message) = message {
+                    result.id = Some(message.id.to_string());
+                    result.url = Some(message.link());
+                }
+
+                info!("Sent Discord webhook!");
+                Ok(())
+            }
+            Err(err) => {
+                Err(PeonyError::Network(format!("Failed to send Discord webhook: {}", err)).into())
+            }
+        }
+    }
+
+    async fn rollback(
+        &self,
+        _release: &Release,
+        result: &mut PublishResult,
+    ) -> Result<(), anyhow::Error> {
+        let message_id = match &result.id {
+            Some(id) => match id.parse::<u64>() {
+                Ok(id) => MessageId(id),
+                Err(err) => return Err(anyhow!("Failed to parse Discord message id: {}", err)),
+            },
+            None => return Ok(()),
+        };
+
+        let http = serenity::http::Http::new("token");
+        let url = get_webhook_url()?;
+
+        let webhook = get_webhook(&http, &url).await?;
+
+        info!("Deleting Discord message `{}`...", message_id);
+
+        match webhook.delete_message(&http, message_id).await {
+            Ok(_) => {
+                info!("Deleted Discord message `{}`!", message_id);
+                Ok(())
+            }
+            Err(err) => Err(PeonyError::Network(format!(
+                "Failed to delete Discord message: {}",
+                err
+            ))
+            .into()),
+        }
+    }
+
+    async fn dry_run(&self, release: &Release) -> Result<Vec<DryRunRequest>, anyhow::Error> {
+        let embed = self.create_embed(release, true).await?;
+
+        Ok(vec![dry_run_request(
+            "Discord webhook",
+            &json!({
+                "content": self.config.discord_ping_role,
+                "embeds": [embed],
+            }),
+        )?])
     }
 }
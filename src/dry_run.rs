@@ -0,0 +1,84 @@
+use anyhow::anyhow;
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+use crate::models::release::Release;
+
+// What a release would have published, returned in the release summary
+// instead of being published
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DryRun {
+    pub assets: Vec<DryRunAsset>,
+    pub requests: Vec<DryRunRequest>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DryRunAsset {
+    pub file_name: String,
+    // In bytes
+    pub size: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DryRunRequest {
+    pub title: String,
+    pub body: serde_json::Value,
+}
+
+pub fn dry_run_request<T: Serialize>(
+    title: &str,
+    value: &T,
+) -> Result<DryRunRequest, anyhow::Error> {
+    match serde_json::to_value(value) {
+        Ok(body) => Ok(DryRunRequest {
+            title: title.to_owned(),
+            body,
+        }),
+        Err(err) => Err(anyhow!("Failed to serialize {}: {}", title, err)),
+    }
+}
+
+pub fn release_assets(release: &Release) -> Result<Vec<DryRunAsset>, anyhow::Error> {
+    let mut assets = vec![];
+
+    for file in &release.files {
+        let file_size = match fs::metadata(&file.file_path) {
+            Ok(metadata) => metadata.len(),
+            Err(err) => {
+                return Err(anyhow!(
+                    "Failed to read metadata of `{}`: {}",
+                    file.file_name,
+                    err
+                ))
+            }
+        };
+
+        assets.push(DryRunAsset {
+            file_name: file.file_name.clone(),
+            size: file_size,
+        })
+    }
+
+    Ok(assets)
+}
+
+pub fn print_dry_run(dry_run: &DryRun) -> Result<(), anyhow::Error> {
+    println!("==> Release assets");
+
+    for asset in &dry_run.assets {
+        println!("{} ({} bytes)", asset.file_name, asset.size);
+    }
+
+    println!();
+
+    for request in &dry_run.requests {
+        let json_string = match serde_json::to_string_pretty(&request.body) {
+            Ok(json) => json,
+            Err(err) => return Err(anyhow!("Failed to serialize {}: {}", request.title, err)),
+        };
+
+        println!("==> {}\n{}\n", request.title, json_string);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,81 @@
+use reqwest::StatusCode;
+use thiserror::Error;
+
+// Errors scripts wrapping Peony need to tell apart. Anything else is a plain
+// `anyhow::Error` and exits with code 1.
+#[derive(Debug, Error)]
+pub enum PeonyError {
+    #[error("{0}")]
+    Config(String),
+    #[error("{0}")]
+    Build(String),
+    #[error("{0}")]
+    Artifact(String),
+    #[error("{0}")]
+    Auth(String),
+    // The release, or part of it, already exists
+    #[error("{0}")]
+    Conflict(String),
+    #[error("{0}")]
+    Network(String),
+}
+
+impl PeonyError {
+    // These are part of Peony's interface, don't change them
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            Self::Config(_) => 3,
+            Self::Build(_) => 4,
+            Self::Artifact(_) => 5,
+            Self::Auth(_) => 6,
+            Self::Conflict(_) => 7,
+            Self::Network(_) => 8,
+        }
+    }
+
+    // Classifies a failed API response by its status code. `message` should
+    // include the response body, since GitHub answers any invalid request
+    // with 422 and only says in the body if something already exists.
+    pub fn from_status(status: StatusCode, message: String) -> anyhow::Error {
+        match status {
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => Self::Auth(message).into(),
+            StatusCode::CONFLICT => Self::Conflict(message).into(),
+            StatusCode::UNPROCESSABLE_ENTITY if message.contains("already_exists") => {
+                Self::Conflict(message).into()
+            }
+            StatusCode::TOO_MANY_REQUESTS => Self::Network(message).into(),
+            status if status.is_server_error() => Self::Network(message).into(),
+            _ => anyhow::Error::msg(message),
+        }
+    }
+}
+
+pub fn exit_code(err: &anyhow::Error) -> u8 {
+    match err
+        .chain()
+        .find_map(|cause| cause.downcast_ref::<PeonyError>())
+    {
+        Some(peony_err) => peony_err.exit_code(),
+        None => 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unprocessable_entity_is_only_a_conflict_if_it_already_exists() {
+        let exists = PeonyError::from_status(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            r#"{"message":"Validation Failed","errors":[{"code":"already_exists"}]}"#.to_owned(),
+        );
+        let invalid = PeonyError::from_status(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            r#"{"message":"Validation Failed","errors":[{"code":"invalid"}]}"#.to_owned(),
+        );
+
+        assert_eq!(exit_code(&exists), 7);
+        assert_eq!(exit_code(&invalid), 1);
+    }
+}
@@ -1,23 +1,30 @@
 use anyhow::anyhow;
+use async_trait::async_trait;
+use reqwest::StatusCode;
+use std::env;
+use std::path::Path;
 use std::process::Command;
-use std::{env, fs};
-
-use crate::models::project_type::mc_mod::config::ModConfig;
-use crate::models::project_type::mc_mod::{ModInfo, ModJars};
-use crate::models::{
-    github::*,
-    project_type::modpack::{config::ModpackConfig, PackFile},
-    util::OutputFileInfo,
-    version::VersionInfo,
-    GithubConfig,
-};
+use tracing::info;
+
+use crate::dry_run::{dry_run_request, DryRunRequest};
+use crate::error::PeonyError;
 use crate::models::modrinth::version::VersionType;
+use crate::models::release::{PublishResult, PublishedFile, Release, ReleaseFile};
+use crate::models::target::Target;
+use crate::models::{github::*, GithubConfig};
+use crate::progress::{file_body, file_size, upload_progress_bar};
+use crate::publisher::Publisher;
+use crate::util::send_request;
 
-pub async fn generate_changelog(config: &GithubConfig) -> Result<String, anyhow::Error> {
-    println!("Generating changelog...");
+pub async fn generate_changelog(
+    config: &GithubConfig,
+    project_dir: &Path,
+) -> Result<String, anyhow::Error> {
+    info!("Generating changelog...");
 
     let first_commit = match Command::new("git")
         .args(["rev-list", "--max-parents=0", "HEAD"])
+        .current_dir(project_dir)
         .output()
     {
         Ok(output) => match String::from_utf8(output.stdout) {
@@ -27,19 +34,17 @@ pub async fn generate_changelog(config: &GithubConfig) -> Result<String, anyhow:
         Err(err) => return Err(anyhow!("Failed to get first commit: {}", err)),
     };
 
-    let latest_release = match reqwest::Client::new()
-        .get(format!(
-            "https://api.github.com/repos/{}/{}/releases/latest",
-            config.repo_owner, config.repo_name
-        ))
-        .header("User-Agent", env!("CARGO_PKG_NAME"))
-        .send()
-        .await
+    let latest_release = match send_request(
+        reqwest::Client::new()
+            .get(format!(
+                "https://api.github.com/repos/{}/{}/releases/latest",
+                config.repo_owner, config.repo_name
+            ))
+            .header("User-Agent", env!("CARGO_PKG_NAME")),
+    )
+    .await
     {
-        Ok(res) => match res.json::<ReleaseResponse>().await {
-            Ok(json) => Some(json),
-            Err(_) => None,
-        },
+        Ok(res) => res.json::<ReleaseResponse>().await.ok(),
         Err(_) => None,
     };
 
@@ -53,234 +58,385 @@ pub async fn generate_changelog(config: &GithubConfig) -> Result<String, anyhow:
         config.repo_owner, config.repo_name, compare_first
     );
 
-    println!("Successfully generated changelog!");
+    info!("Successfully generated changelog!");
 
     Ok(format!("[Full Changelog]({})", full_changelog))
 }
 
-pub async fn create_modpack_release(
-    config: &ModpackConfig,
-    pack_file: &PackFile,
-    output_file_info: &OutputFileInfo,
-    version_info: &VersionInfo,
-    changelog: &str,
-    version_type: VersionType
-) -> Result<(), anyhow::Error> {
-    println!("Creating GitHub release...");
-
-    let github_token = match env::var("GITHUB_TOKEN") {
-        Ok(token) => token,
-        Err(err) => return Err(anyhow!("Failed to get `GITHUB_TOKEN`: {}", err)),
-    };
-
-    let new_release_req_body = CreateReleaseRequest {
-        tag_name: pack_file.version.clone(),
-        name: Some(version_info.version_name.clone()),
-        body: Some(changelog.to_owned()),
-        prerelease: match version_type {
-            VersionType::Release => false,
-            _ => true
-        }
-    };
+pub struct GithubPublisher {
+    pub config: GithubConfig,
+}
 
-    let new_release_response =
-        create_github_release(&config.github, &new_release_req_body, &github_token).await;
-
-    match new_release_response {
-        Ok(release_res) => {
-            println!("Successfully created GitHub release!");
-            println!("Uploading release asset to GitHub release...");
-
-            match reqwest::Client::new()
-                .post(format!(
-                    "https://uploads.github.com/repos/{}/{}/releases/{}/assets?name=\"{}\"",
-                    config.github.repo_owner,
-                    config.github.repo_name,
-                    release_res.id,
-                    &output_file_info.file_name
-                ))
-                .header("User-Agent", env!("CARGO_PKG_NAME"))
-                .header("Accept", "application/vnd.github+json")
-                .header("Content-Type", "application/zip")
-                .bearer_auth(github_token)
-                .body(version_info.file_contents.clone())
-                .send()
-                .await
-            {
-                Ok(_) => {
-                    println!("Successfully uploaded release asset!");
-                    Ok(())
-                }
-                Err(err) => Err(anyhow::Error::from(err)),
-            }
-        }
-        Err(err) => Err(err),
+pub fn get_github_token() -> Result<String, anyhow::Error> {
+    match env::var("GITHUB_TOKEN") {
+        Ok(token) => Ok(token),
+        Err(err) => Err(PeonyError::Auth(format!("Failed to get `GITHUB_TOKEN`: {}", err)).into()),
     }
 }
 
-pub async fn create_mod_release(
-    config: &ModConfig,
-    mod_info: &ModInfo,
-    mod_jars: &ModJars,
-    changelog: &str,
-    version_name: &String,
-    version_type: VersionType
-) -> Result<(), anyhow::Error> {
-    println!("Creating GitHub release...");
-
-    let github_token = match env::var("GITHUB_TOKEN") {
-        Ok(token) => token,
-        Err(err) => return Err(anyhow!("Failed to get `GITHUB_TOKEN`: {}", err)),
-    };
+pub fn release_request(release: &Release) -> CreateReleaseRequest {
+    CreateReleaseRequest {
+        tag_name: release.version_number.clone(),
+        name: Some(release.version_name.clone()),
+        body: Some(release.changelog.clone()),
+        prerelease: !matches!(release.version_type, VersionType::Release),
+    }
+}
 
-    let new_release_req_body = CreateReleaseRequest {
-        tag_name: mod_info.version.clone(),
-        name: Some(version_name.into()),
-        body: Some(changelog.to_owned()),
-        prerelease: match version_type {
-            VersionType::Release => false,
-            _ => true
+pub async fn create_github_release(
+    config: &GithubConfig,
+    new_release_body: &CreateReleaseRequest,
+    token: &String,
+) -> Result<ReleaseResponse, anyhow::Error> {
+    let res = match send_request(
+        reqwest::Client::new()
+            .post(format!(
+                "https://api.github.com/repos/{}/{}/releases",
+                config.repo_owner.clone(),
+                config.repo_name.clone()
+            ))
+            .json(&new_release_body)
+            .header("User-Agent", env!("CARGO_PKG_NAME"))
+            .header("Accept", "application/vnd.github+json")
+            .bearer_auth(token),
+    )
+    .await
+    {
+        Ok(res) => res,
+        Err(err) => {
+            return Err(
+                PeonyError::Network(format!("Failed to create GitHub release: {}", err)).into(),
+            )
         }
     };
 
-    let new_release_response =
-        create_github_release(&config.github, &new_release_req_body, &github_token).await;
+    let status = res.status();
 
-    upload_mod_jars(
-        mod_jars,
-        &config.github,
-        new_release_response.unwrap().id,
-        github_token,
-    )
-    .await
+    if !status.is_success() {
+        return Err(PeonyError::from_status(
+            status,
+            format!(
+                "Failed to create GitHub release: {}",
+                res.text().await.unwrap_or_default()
+            ),
+        ));
+    }
+
+    match res.json::<ReleaseResponse>().await {
+        Ok(json) => Ok(json),
+        Err(err) => Err(anyhow::Error::from(err)),
+    }
 }
 
-pub async fn upload_mod_jars(
-    mod_jars: &ModJars,
-    github_config: &GithubConfig,
-    release_id: i32,
-    token: String,
-) -> Result<(), anyhow::Error> {
-    let mod_jar_contents = match fs::read(&mod_jars.mod_jar.file_path) {
-        Ok(file_contents) => file_contents,
-        Err(err) => return Err(anyhow!("Failed to read mod jar: {}", err)),
-    };
+pub async fn upload_release_asset(
+    config: &GithubConfig,
+    release_id: &str,
+    file: &ReleaseFile,
+    token: &String,
+) -> Result<GithubAsset, anyhow::Error> {
+    info!("Uploading GitHub Release asset `{}`...", &file.file_name);
 
-    let sources_jar_contents = match &mod_jars.sources_jar {
-        Some(jar) => match fs::read(&jar.file_path) {
-            Ok(file_contents) => Some(file_contents),
-            Err(err) => {
-                return Err(anyhow!(
-                    "Failed to read sources jar, even though it exists: {}",
-                    err
-                ))
-            }
-        },
-        None => None,
+    let file_size = file_size(&file.file_path)?;
+    let progress_bar = upload_progress_bar(file_size, format!("Uploading `{}`", file.file_name));
+
+    let res = match send_request(
+        reqwest::Client::new()
+            .post(format!(
+                "https://uploads.github.com/repos/{}/{}/releases/{}/assets",
+                config.repo_owner, config.repo_name, release_id
+            ))
+            .query(&[("name", &file.file_name)])
+            .header("User-Agent", env!("CARGO_PKG_NAME"))
+            .header("Accept", "application/vnd.github+json")
+            .header("Content-Type", file.file_type.content_type())
+            // GitHub rejects uploads without a length, which a streamed body
+            // doesn't have
+            .header("Content-Length", file_size)
+            .bearer_auth(token)
+            .body(file_body(&file.file_path, &progress_bar).await?),
+    )
+    .await
+    {
+        Ok(res) => res,
+        Err(err) => {
+            return Err(PeonyError::Network(format!(
+                "Failed to upload GitHub release asset `{}`: {}",
+                &file.file_name, err
+            ))
+            .into())
+        }
     };
 
-    // Upload mod jar
+    let status = res.status();
 
-    println!(
-        "Uploading mod jar as GitHub Release asset `{}`...",
-        &mod_jars.mod_jar.file_name
-    );
+    if !status.is_success() {
+        return Err(PeonyError::from_status(
+            status,
+            format!(
+                "Failed to upload GitHub release asset `{}`: {}",
+                &file.file_name,
+                res.text().await.unwrap_or_default()
+            ),
+        ));
+    }
 
-    match reqwest::Client::new()
-        .post(format!(
-            "https://uploads.github.com/repos/{}/{}/releases/{}/assets?name=\"{}\"",
-            github_config.repo_owner,
-            github_config.repo_name,
-            release_id,
-            &mod_jars.mod_jar.file_name
-        ))
-        .header("User-Agent", env!("CARGO_PKG_NAME"))
-        .header("Accept", "application/vnd.github+json")
-        .header("Content-Type", "application/java-archive")
-        .bearer_auth(&token)
-        .body(mod_jar_contents)
-        .send()
-        .await
-    {
-        Ok(_) => {
-            println!(
+    match res.json::<GithubAsset>().await {
+        Ok(asset) => {
+            info!(
                 "Successfully uploaded GitHub Release asset `{}`!",
-                &mod_jars.mod_jar.file_name
-            )
+                &file.file_name
+            );
+            Ok(asset)
         }
+        Err(err) => Err(anyhow!(
+            "Failed to parse GitHub release asset `{}`: {}",
+            &file.file_name,
+            err
+        )),
+    }
+}
+
+async fn get_github_status(url: &str, token: &String) -> Result<StatusCode, anyhow::Error> {
+    match send_request(
+        reqwest::Client::new()
+            .get(url)
+            .header("User-Agent", env!("CARGO_PKG_NAME"))
+            .header("Accept", "application/vnd.github+json")
+            .bearer_auth(token),
+    )
+    .await
+    {
+        Ok(res) => Ok(res.status()),
         Err(err) => {
-            return Err(anyhow!(
-                "Failed to upload GitHub release asset `{}`: {}",
-                &mod_jars.mod_jar.file_name,
-                err
-            ))
+            Err(PeonyError::Network(format!("Failed to send request to `{}`: {}", url, err)).into())
         }
     }
+}
 
-    // Upload sources jar
-    if let Some(file_contents) = sources_jar_contents {
-        println!(
-            "Uploading sources jar as GitHub Release asset `{}`...",
-            &mod_jars.sources_jar.clone().unwrap().file_name
-        );
+async fn github_tag_exists(
+    config: &GithubConfig,
+    tag: &str,
+    token: &String,
+) -> Result<bool, anyhow::Error> {
+    match get_github_status(
+        &format!(
+            "https://api.github.com/repos/{}/{}/git/ref/tags/{}",
+            config.repo_owner, config.repo_name, tag
+        ),
+        token,
+    )
+    .await?
+    {
+        StatusCode::NOT_FOUND => Ok(false),
+        status if status.is_success() => Ok(true),
+        status => Err(PeonyError::from_status(
+            status,
+            format!("Failed to check GitHub tag `{}`: {}", tag, status),
+        )),
+    }
+}
 
-        match reqwest::Client::new()
-            .post(format!(
-                "https://uploads.github.com/repos/{}/{}/releases/{}/assets?name=\"{}\"",
-                github_config.repo_owner,
-                github_config.repo_name,
-                release_id,
-                &mod_jars.sources_jar.clone().unwrap().file_name
-            ))
+async fn delete_github_resource(url: String, token: &String) -> Result<(), anyhow::Error> {
+    let res = match send_request(
+        reqwest::Client::new()
+            .delete(&url)
             .header("User-Agent", env!("CARGO_PKG_NAME"))
             .header("Accept", "application/vnd.github+json")
-            .header("Content-Type", "application/java-archive")
-            .bearer_auth(&token)
-            .body(file_contents)
-            .send()
-            .await
-        {
-            Ok(_) => {
-                println!(
-                    "Successfully uploaded GitHub Release asset `{}`!",
-                    &mod_jars.sources_jar.clone().unwrap().file_name
-                )
+            .bearer_auth(token),
+    )
+    .await
+    {
+        Ok(res) => res,
+        Err(err) => {
+            return Err(PeonyError::Network(format!(
+                "Failed to send delete request to `{}`: {}",
+                url, err
+            ))
+            .into())
+        }
+    };
+
+    let status = res.status();
+
+    if status.is_success() {
+        Ok(())
+    } else {
+        Err(PeonyError::from_status(
+            status,
+            format!(
+                "Failed to delete `{}`: {}",
+                url,
+                res.text().await.unwrap_or_default()
+            ),
+        ))
+    }
+}
+
+#[async_trait]
+impl Publisher for GithubPublisher {
+    fn target(&self) -> Target {
+        Target::Github
+    }
+
+    async fn preflight(&self, version_number: Option<&str>) -> Result<(), anyhow::Error> {
+        let github_token = get_github_token()?;
+
+        let repo_url = format!(
+            "https://api.github.com/repos/{}/{}",
+            self.config.repo_owner, self.config.repo_name
+        );
+
+        match get_github_status(&repo_url, &github_token).await? {
+            status if status.is_success() => (),
+            StatusCode::NOT_FOUND => {
+                return Err(PeonyError::Config(format!(
+                    "GitHub repo `{}/{}` doesn't exist or `GITHUB_TOKEN` can't access it",
+                    self.config.repo_owner, self.config.repo_name
+                ))
+                .into())
             }
-            Err(err) => {
-                return Err(anyhow!(
-                    "Failed to upload GitHub release asset `{}`: {}",
-                    &mod_jars.sources_jar.clone().unwrap().file_name,
-                    err
+            status => {
+                return Err(PeonyError::from_status(
+                    status,
+                    format!("Failed to check GitHub repo: {}", status),
                 ))
             }
         }
+
+        if let Some(tag) = version_number {
+            match get_github_status(
+                &format!("{}/releases/tags/{}", repo_url, tag),
+                &github_token,
+            )
+            .await?
+            {
+                StatusCode::NOT_FOUND => (),
+                status if status.is_success() => {
+                    return Err(PeonyError::Conflict(format!(
+                        "GitHub release `{}` already exists",
+                        tag
+                    ))
+                    .into())
+                }
+                status => {
+                    return Err(PeonyError::from_status(
+                        status,
+                        format!("Failed to check GitHub release: {}", status),
+                    ))
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn publish(
+        &self,
+        release: &Release,
+        result: &mut PublishResult,
+    ) -> Result<(), anyhow::Error> {
+        let github_token = get_github_token()?;
+
+        let release_id = match &result.id {
+            Some(id) => id.clone(),
+            None => {
+                // Creating the release creates its tag if it doesn't exist yet.
+                // A resumed release keeps the tag it created the first time.
+                if !github_tag_exists(&self.config, &release.version_number, &github_token).await? {
+                    result.created_tag = true
+                }
+
+                info!("Creating GitHub release...");
+
+                let release_res =
+                    create_github_release(&self.config, &release_request(release), &github_token)
+                        .await?;
+
+                info!("Successfully created GitHub release!");
+
+                result.id = Some(release_res.id.to_string());
+                result.url = Some(release_res.html_url);
+
+                release_res.id.to_string()
+            }
+        };
+
+        for file in &release.files {
+            if result.has_file(&file.file_name) {
+                continue;
+            }
+
+            let asset =
+                upload_release_asset(&self.config, &release_id, file, &github_token).await?;
+
+            result.files.push(PublishedFile {
+                file_name: file.file_name.clone(),
+                url: Some(asset.browser_download_url),
+                sha512: file.sha512.clone(),
+            });
+        }
+
+        Ok(())
     }
 
-    Ok(())
-}
+    async fn rollback(
+        &self,
+        release: &Release,
+        result: &mut PublishResult,
+    ) -> Result<(), anyhow::Error> {
+        let release_id = match &result.id {
+            Some(id) => id.clone(),
+            None => return Ok(()),
+        };
 
-pub async fn create_github_release(
-    config: &GithubConfig,
-    new_release_body: &CreateReleaseRequest,
-    token: &String,
-) -> Result<ReleaseResponse, anyhow::Error> {
-    match reqwest::Client::new()
-        .post(format!(
-            "https://api.github.com/repos/{}/{}/releases",
-            config.repo_owner.clone(),
-            config.repo_name.clone()
-        ))
-        .json(&new_release_body)
-        .header("User-Agent", env!("CARGO_PKG_NAME"))
-        .header("Accept", "application/vnd.github+json")
-        .bearer_auth(token)
-        .send()
-        .await
-    {
-        Ok(res) => match res.json::<ReleaseResponse>().await {
-            Ok(json) => Ok(json),
-            Err(err) => Err(anyhow::Error::from(err)),
-        },
-        Err(err) => Err(anyhow::Error::from(err)),
+        let github_token = get_github_token()?;
+
+        // Deleting a release also deletes its assets, but leaves its tag behind
+        info!("Deleting GitHub release `{}`...", release_id);
+
+        delete_github_resource(
+            format!(
+                "https://api.github.com/repos/{}/{}/releases/{}",
+                self.config.repo_owner, self.config.repo_name, release_id
+            ),
+            &github_token,
+        )
+        .await?;
+
+        // The release is gone even if deleting its tag fails, so resuming
+        // has to create it again
+        result.id = None;
+        result.url = None;
+        result.files.clear();
+        result.completed = false;
+
+        if result.created_tag {
+            info!("Deleting tag `{}`...", release.version_number);
+
+            delete_github_resource(
+                format!(
+                    "https://api.github.com/repos/{}/{}/git/refs/tags/{}",
+                    self.config.repo_owner, self.config.repo_name, release.version_number
+                ),
+                &github_token,
+            )
+            .await?;
+        } else {
+            info!(
+                "Keeping tag `{}`, it existed before the release",
+                release.version_number
+            );
+        }
+
+        info!("Deleted GitHub release `{}`!", release_id);
+
+        Ok(())
+    }
+
+    async fn dry_run(&self, release: &Release) -> Result<Vec<DryRunRequest>, anyhow::Error> {
+        Ok(vec![dry_run_request(
+            "GitHub release request",
+            &release_request(release),
+        )?])
     }
 }
@@ -0,0 +1,226 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::env;
+use std::path::Path;
+use std::process::Stdio;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::process::Command;
+use tracing::info;
+
+use crate::error::PeonyError;
+use crate::models::project_type::mc_mod::config::GradleConfig;
+
+pub const DEFAULT_GRADLE_TASKS: [&str; 1] = ["build"];
+
+// How many lines of Gradle's output are included in a build failure
+const OUTPUT_TAIL_LINES: usize = 30;
+
+#[derive(Debug, Clone, Default)]
+pub struct GradleOptions {
+    pub tasks: Vec<String>,
+    // Passed as `-Pkey=value`
+    pub properties: BTreeMap<String, String>,
+    pub timeout: Option<Duration>,
+}
+
+impl GradleOptions {
+    // Tasks given on the command line replace the configured ones, while
+    // properties are merged with the command line's taking precedence
+    pub fn new(
+        config: Option<&GradleConfig>,
+        tasks: Vec<String>,
+        properties: Vec<(String, String)>,
+        timeout: Option<u64>,
+    ) -> Self {
+        let tasks = if !tasks.is_empty() {
+            tasks
+        } else {
+            match config.and_then(|config| config.tasks.clone()) {
+                Some(tasks) => tasks,
+                None => DEFAULT_GRADLE_TASKS.iter().map(|t| t.to_string()).collect(),
+            }
+        };
+
+        let mut merged_properties = config
+            .and_then(|config| config.properties.clone())
+            .unwrap_or_default();
+        merged_properties.extend(properties);
+
+        Self {
+            tasks,
+            properties: merged_properties,
+            timeout: timeout
+                .or(config.and_then(|config| config.timeout))
+                .map(Duration::from_secs),
+        }
+    }
+
+    pub fn args(&self) -> Vec<String> {
+        let mut args = self.tasks.clone();
+
+        for (key, value) in &self.properties {
+            args.push(format!("-P{}={}", key, value))
+        }
+
+        args
+    }
+}
+
+pub fn gradlew_name() -> &'static str {
+    if env::consts::OS == "windows" {
+        "gradlew.bat"
+    } else {
+        "gradlew"
+    }
+}
+
+// Gradle's output is logged line by line, so it follows `--quiet` and
+// `--log-format`, and the last lines of it are kept for the error if the
+// build fails.
+pub async fn run_gradle(project_dir: &Path, options: &GradleOptions) -> Result<(), anyhow::Error> {
+    info!("Building with Gradle ({})...", options.tasks.join(" "));
+
+    let mut child = match Command::new(project_dir.join(gradlew_name()))
+        .args(options.args())
+        .current_dir(project_dir)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(err) => {
+            return Err(PeonyError::Build(format!("Failed to build with Gradle: {}", err)).into())
+        }
+    };
+
+    let output_tail = Arc::new(Mutex::new(VecDeque::new()));
+
+    let stdout_task = child
+        .stdout
+        .take()
+        .map(|stdout| tokio::spawn(forward_output(stdout, output_tail.clone())));
+    let stderr_task = child
+        .stderr
+        .take()
+        .map(|stderr| tokio::spawn(forward_output(stderr, output_tail.clone())));
+
+    let wait_res = match options.timeout {
+        Some(timeout) => match tokio::time::timeout(timeout, child.wait()).await {
+            Ok(wait_res) => wait_res,
+            Err(_) => {
+                let _ = child.kill().await;
+
+                return Err(PeonyError::Build(format!(
+                    "Gradle build timed out after {} seconds",
+                    timeout.as_secs()
+                ))
+                .into());
+            }
+        },
+        None => child.wait().await,
+    };
+
+    for task in [stdout_task, stderr_task].into_iter().flatten() {
+        let _ = task.await;
+    }
+
+    let status = match wait_res {
+        Ok(status) => status,
+        Err(err) => {
+            return Err(PeonyError::Build(format!("Failed to build with Gradle: {}", err)).into())
+        }
+    };
+
+    if !status.success() {
+        let output_tail = match output_tail.lock() {
+            Ok(lines) => lines.iter().cloned().collect::<Vec<String>>().join("\n"),
+            Err(_) => String::new(),
+        };
+
+        return Err(PeonyError::Build(format!(
+            "Gradle build failed ({}), last lines of output:\n{}",
+            status, output_tail
+        ))
+        .into());
+    }
+
+    info!("Successfully built with Gradle!");
+
+    Ok(())
+}
+
+async fn forward_output<R: AsyncRead + Unpin>(
+    output: R,
+    output_tail: Arc<Mutex<VecDeque<String>>>,
+) {
+    let mut lines = BufReader::new(output).lines();
+
+    while let Ok(Some(line)) = lines.next_line().await {
+        info!(target: "peony::gradle::output", "{}", line);
+
+        if let Ok(mut tail) = output_tail.lock() {
+            if tail.len() == OUTPUT_TAIL_LINES {
+                tail.pop_front();
+            }
+
+            tail.push_back(line)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn uses_config_without_overrides() {
+        let config = GradleConfig {
+            tasks: Some(vec!["shadowJar".to_owned()]),
+            properties: Some(BTreeMap::from([("a".to_owned(), "1".to_owned())])),
+            timeout: Some(60),
+        };
+
+        let options = GradleOptions::new(Some(&config), vec![], vec![], None);
+
+        assert_eq!(options.tasks, vec!["shadowJar"]);
+        assert_eq!(options.timeout, Some(Duration::from_secs(60)));
+        assert_eq!(options.args(), vec!["shadowJar", "-Pa=1"]);
+    }
+
+    #[test]
+    fn merges_command_line_overrides() {
+        let config = GradleConfig {
+            tasks: Some(vec!["shadowJar".to_owned()]),
+            properties: Some(BTreeMap::from([
+                ("a".to_owned(), "1".to_owned()),
+                ("b".to_owned(), "2".to_owned()),
+            ])),
+            timeout: Some(60),
+        };
+
+        let options = GradleOptions::new(
+            Some(&config),
+            vec!["build".to_owned(), "javadocJar".to_owned()],
+            vec![("b".to_owned(), "3".to_owned())],
+            Some(10),
+        );
+
+        assert_eq!(options.tasks, vec!["build", "javadocJar"]);
+        assert_eq!(options.timeout, Some(Duration::from_secs(10)));
+        assert_eq!(
+            options.args(),
+            vec!["build", "javadocJar", "-Pa=1", "-Pb=3"]
+        );
+    }
+
+    #[test]
+    fn defaults_without_config() {
+        let options = GradleOptions::new(None, vec![], vec![], None);
+
+        assert_eq!(options.tasks, DEFAULT_GRADLE_TASKS);
+        assert!(options.properties.is_empty());
+        assert_eq!(options.timeout, None);
+    }
+}
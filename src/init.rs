@@ -0,0 +1,307 @@
+use anyhow::anyhow;
+use clap::ValueEnum;
+use std::io::Write;
+use std::path::Path;
+use std::process::Command;
+use std::{fs, io};
+use tracing::{info, warn};
+
+use crate::config::{CONFIG_FORMAT_VERSION, MODPACK_CONFIG_FILE, MOD_CONFIG_FILE};
+use crate::loader_metadata::{parse_loader_metadata, METADATA_FILES};
+use crate::models::modrinth::Loader;
+use crate::models::project_type::mc_mod::config::{modrinth::ModrinthConfig, ModConfig};
+use crate::models::project_type::modpack::config::ModpackConfig;
+use crate::models::{GithubConfig, ModrinthConfig as ModpackModrinthConfig};
+use crate::pack::get_pack_file;
+
+pub const MOD_VERSION_NAME_FORMAT: &str =
+    "[%mc_version%] %project_name% v%project_version% - %loader%";
+pub const MODPACK_VERSION_NAME_FORMAT: &str =
+    "%project_name% v%project_version% for Minecraft %mc_version% (%loader%)";
+
+pub struct Prompter {
+    pub interactive: bool,
+}
+
+impl Prompter {
+    pub fn ask(&self, question: &str, default: &str) -> Result<String, anyhow::Error> {
+        if !self.interactive {
+            return Ok(default.to_owned());
+        }
+
+        print!("{} [{}]: ", question, default);
+        io::stdout().flush()?;
+
+        let mut answer = String::new();
+        match io::stdin().read_line(&mut answer) {
+            Ok(_) => (),
+            Err(err) => return Err(anyhow!("Failed to read answer: {}", err)),
+        }
+
+        let answer = answer.trim();
+
+        if answer.is_empty() {
+            Ok(default.to_owned())
+        } else {
+            Ok(answer.to_owned())
+        }
+    }
+
+    pub fn ask_list(
+        &self,
+        question: &str,
+        default: &[String],
+    ) -> Result<Vec<String>, anyhow::Error> {
+        Ok(self
+            .ask(question, &default.join(", "))?
+            .split(',')
+            .map(|item| item.trim().to_owned())
+            .filter(|item| !item.is_empty())
+            .collect())
+    }
+}
+
+// Returns the path of the created config
+pub fn init_project(interactive: bool, force: bool) -> Result<&'static str, anyhow::Error> {
+    let prompter = Prompter { interactive };
+    let github_config = get_github_remote().unwrap_or(GithubConfig {
+        repo_owner: String::new(),
+        repo_name: String::new(),
+    });
+
+    let is_modpack = if Path::new("pack.toml").exists() {
+        info!("Found `pack.toml`, creating modpack config...");
+        true
+    } else if Path::new("gradlew").exists() || Path::new("gradlew.bat").exists() {
+        info!("Found Gradle project, creating mod config...");
+        false
+    } else {
+        return Err(anyhow!(
+            "Failed to detect project type, expected a `pack.toml` or `gradlew` file"
+        ));
+    };
+
+    let config_path = if is_modpack {
+        MODPACK_CONFIG_FILE
+    } else {
+        MOD_CONFIG_FILE
+    };
+
+    if Path::new(config_path).exists() && !force {
+        return Err(anyhow!(
+            "`{}` already exists, pass `--force` to overwrite it",
+            config_path
+        ));
+    }
+
+    let config_string = if is_modpack {
+        init_modpack_config(&prompter, github_config)?
+    } else {
+        init_mod_config(&prompter, github_config)?
+    };
+
+    match fs::write(config_path, config_string) {
+        Ok(_) => Ok(config_path),
+        Err(err) => Err(anyhow!("Failed to write `{}`: {}", config_path, err)),
+    }
+}
+
+fn init_modpack_config(
+    prompter: &Prompter,
+    github_config: GithubConfig,
+) -> Result<String, anyhow::Error> {
+    // The loader and Minecraft version are read from `pack.toml` on every
+    // release, so they don't need to be part of the config.
+    let pack_file = get_pack_file(Path::new("."))?;
+
+    info!(
+        "Detected `{}` for Minecraft {}",
+        pack_file.name, pack_file.versions.minecraft
+    );
+
+    let config = ModpackConfig {
+        config_format_version: CONFIG_FORMAT_VERSION,
+        version_name_format: prompter.ask("Version name format", MODPACK_VERSION_NAME_FORMAT)?,
+        github: GithubConfig {
+            repo_owner: prompter.ask("GitHub repo owner", &github_config.repo_owner)?,
+            repo_name: prompter.ask("GitHub repo name", &github_config.repo_name)?,
+        },
+        modrinth: ModpackModrinthConfig {
+            project_id: prompter.ask("Modrinth project id", "")?,
+            staging: Some(false),
+        },
+        discord: None,
+        rollback: None,
+        profiles: None,
+    };
+
+    match toml::to_string(&config) {
+        Ok(config_string) => Ok(config_string),
+        Err(err) => Err(anyhow!("Failed to serialize modpack config: {}", err)),
+    }
+}
+
+fn init_mod_config(
+    prompter: &Prompter,
+    github_config: GithubConfig,
+) -> Result<String, anyhow::Error> {
+    let (loaders, mc_versions) = read_mod_metadata()?;
+
+    let loaders = prompter
+        .ask_list(
+            "Loaders",
+            &loaders
+                .iter()
+                .map(|loader| loader.formatted().to_lowercase())
+                .collect::<Vec<String>>(),
+        )?
+        .iter()
+        .map(|loader| match Loader::from_str(loader, true) {
+            Ok(loader) => Ok(loader),
+            Err(err) => Err(anyhow!("Failed to parse loader `{}`: {}", loader, err)),
+        })
+        .collect::<Result<Vec<Loader>, anyhow::Error>>()?;
+
+    let mc_versions = prompter.ask_list("Minecraft versions", &mc_versions)?;
+    let mc_version_alias = prompter.ask(
+        "Minecraft version alias",
+        mc_versions.first().map_or("", |version| version.as_str()),
+    )?;
+
+    let config = ModConfig {
+        config_format_version: CONFIG_FORMAT_VERSION,
+        version_name_format: prompter.ask("Version name format", MOD_VERSION_NAME_FORMAT)?,
+        loaders,
+        mc_versions,
+        mc_version_alias,
+        version_alias: None,
+        github: GithubConfig {
+            repo_owner: prompter.ask("GitHub repo owner", &github_config.repo_owner)?,
+            repo_name: prompter.ask("GitHub repo name", &github_config.repo_name)?,
+        },
+        modrinth: ModrinthConfig {
+            project_id: prompter.ask("Modrinth project id", "")?,
+            staging: Some(false),
+            dependencies: None,
+        },
+        discord: None,
+        rollback: None,
+        profiles: None,
+        subprojects: None,
+        artifacts: None,
+        gradle: None,
+    };
+
+    match toml::to_string(&config) {
+        Ok(config_string) => Ok(config_string),
+        Err(err) => Err(anyhow!("Failed to serialize mod config: {}", err)),
+    }
+}
+
+// Reads the loaders and Minecraft versions from the mod's loader metadata
+fn read_mod_metadata() -> Result<(Vec<Loader>, Vec<String>), anyhow::Error> {
+    let resources_dir = Path::new("src").join("main").join("resources");
+
+    let (file_name, loader) = match METADATA_FILES
+        .iter()
+        .find(|(file_name, _)| resources_dir.join(file_name).exists())
+    {
+        Some(metadata_file) => *metadata_file,
+        None => {
+            warn!("Failed to find loader metadata, using defaults");
+            return Ok((vec![], vec![]));
+        }
+    };
+
+    let metadata_path = resources_dir.join(file_name);
+
+    let metadata_string = match fs::read_to_string(&metadata_path) {
+        Ok(file) => file,
+        Err(err) => return Err(anyhow!("Failed to read {:?}: {}", metadata_path, err)),
+    };
+
+    let mod_info = parse_loader_metadata(&metadata_string, file_name, loader)?;
+
+    let ranges: Vec<String> = mod_info
+        .dependencies
+        .into_iter()
+        .filter(|dep| dep.id == "minecraft")
+        .flat_map(|dep| dep.version_ranges)
+        .collect();
+
+    let versions = match mod_info.loader {
+        Loader::Forge | Loader::Neoforge => versions_from_maven_ranges(&ranges),
+        _ => versions_from_ranges(&ranges),
+    };
+
+    Ok((vec![mod_info.loader], versions))
+}
+
+// Forge and NeoForge use Maven version ranges like `[1.20.1,1.21)`
+fn versions_from_maven_ranges(ranges: &[String]) -> Vec<String> {
+    let mut versions = vec![];
+
+    for range in ranges {
+        let lower_bound = range
+            .trim_start_matches(['[', '('])
+            .split(',')
+            .next()
+            .unwrap_or_default()
+            .trim();
+
+        // Ranges are often filled in by Gradle, e.g. `${minecraft_version_range}`
+        if lower_bound.starts_with(|c: char| c.is_ascii_digit())
+            && !versions.contains(&lower_bound.to_owned())
+        {
+            versions.push(lower_bound.to_owned())
+        }
+    }
+
+    versions
+}
+
+// Takes the lower bounds of version ranges like `~1.20` or `>=1.19.4 <1.21`,
+// as those are the only versions that are guaranteed to exist.
+fn versions_from_ranges(ranges: &[String]) -> Vec<String> {
+    let mut versions = vec![];
+
+    for range in ranges {
+        for predicate in range.split_whitespace() {
+            if predicate.starts_with('<') || predicate == "*" {
+                continue;
+            }
+
+            let version = predicate.trim_start_matches(['>', '=', '~', '^']);
+
+            if version.starts_with(|c: char| c.is_ascii_digit())
+                && !versions.contains(&version.to_owned())
+            {
+                versions.push(version.to_owned())
+            }
+        }
+    }
+
+    versions
+}
+
+pub fn get_github_remote() -> Option<GithubConfig> {
+    let output = Command::new("git")
+        .args(["remote", "get-url", "origin"])
+        .output()
+        .ok()?;
+
+    let remote_url = String::from_utf8(output.stdout).ok()?;
+    let remote_url = remote_url.trim().trim_end_matches(".git");
+
+    let repo_path = remote_url
+        .strip_prefix("git@github.com:")
+        .or_else(|| remote_url.strip_prefix("https://github.com/"))
+        .or_else(|| remote_url.strip_prefix("ssh://git@github.com/"))?;
+
+    let (repo_owner, repo_name) = repo_path.split_once('/')?;
+
+    Some(GithubConfig {
+        repo_owner: repo_owner.to_owned(),
+        repo_name: repo_name.to_owned(),
+    })
+}
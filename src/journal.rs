@@ -0,0 +1,238 @@
+use anyhow::anyhow;
+use std::ffi::OsStr;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::error::PeonyError;
+use crate::models::journal::ReleaseJournal;
+use crate::util::sha512_hash;
+
+pub const JOURNAL_DIR: &str = ".peony";
+
+fn journal_name(version_number: &str) -> String {
+    version_number
+        .chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '.' | '-' | '+' | '_' => c,
+            _ => '_',
+        })
+        .collect()
+}
+
+// Journals are kept in the project directory, so projects released from the
+// same working directory don't share them
+pub fn journal_path(project_dir: &Path, version_number: &str) -> PathBuf {
+    project_dir
+        .join(JOURNAL_DIR)
+        .join(format!("release-{}.json", journal_name(version_number)))
+}
+
+fn artifacts_dir(project_dir: &Path, version_number: &str) -> PathBuf {
+    project_dir
+        .join(JOURNAL_DIR)
+        .join("artifacts")
+        .join(journal_name(version_number))
+}
+
+// Copies the built artifacts out of the temporary build directory, so a
+// release can be resumed after the temporary directory is gone.
+pub fn start_journal(
+    journal: &mut ReleaseJournal,
+    project_dir: &Path,
+) -> Result<(), anyhow::Error> {
+    let version_number = journal.release.version_number.clone();
+    journal.path = journal_path(project_dir, &version_number);
+
+    if journal.path.exists() {
+        let existing_journal = load_journal(&journal.path)?;
+
+        if !existing_journal.is_complete()
+            && existing_journal.results.iter().any(|r| r.id.is_some())
+        {
+            return Err(PeonyError::Conflict(format!(
+                "Found an unfinished release of version `{}`, continue it with `peony resume` \
+                 or delete `{}` to start over",
+                version_number,
+                journal.path.display()
+            ))
+            .into());
+        }
+    }
+
+    let artifacts_path = artifacts_dir(project_dir, &version_number);
+
+    match fs::create_dir_all(&artifacts_path) {
+        Ok(_) => (),
+        Err(err) => return Err(anyhow!("Failed to create artifacts directory: {}", err)),
+    }
+
+    // The journal stores absolute paths, so the release can be resumed from
+    // any working directory
+    let artifacts_path = match fs::canonicalize(&artifacts_path) {
+        Ok(path) => path,
+        Err(err) => return Err(anyhow!("Failed to resolve artifacts directory: {}", err)),
+    };
+
+    for file in &mut journal.release.files {
+        let new_path = artifacts_path.join(&file.file_name);
+
+        match fs::copy(&file.file_path, &new_path) {
+            Ok(_) => file.file_path = new_path,
+            Err(err) => {
+                return Err(anyhow!(
+                    "Failed to copy `{}` to artifacts directory: {}",
+                    file.file_name,
+                    err
+                ))
+            }
+        }
+    }
+
+    save_journal(journal)
+}
+
+pub fn save_journal(journal: &ReleaseJournal) -> Result<(), anyhow::Error> {
+    let journal_string = match serde_json::to_string_pretty(journal) {
+        Ok(json) => json,
+        Err(err) => return Err(anyhow!("Failed to serialize release journal: {}", err)),
+    };
+
+    match fs::write(&journal.path, journal_string) {
+        Ok(_) => Ok(()),
+        Err(err) => Err(anyhow!(
+            "Failed to write release journal `{}`: {}",
+            journal.path.display(),
+            err
+        )),
+    }
+}
+
+pub fn load_journal(path: &PathBuf) -> Result<ReleaseJournal, anyhow::Error> {
+    let journal_string = match fs::read_to_string(path) {
+        Ok(file) => file,
+        Err(err) => {
+            return Err(anyhow!(
+                "Failed to read release journal `{}`: {}",
+                path.display(),
+                err
+            ))
+        }
+    };
+
+    let mut journal: ReleaseJournal = match serde_json::from_str(&journal_string) {
+        Ok(journal) => journal,
+        Err(err) => {
+            return Err(anyhow!(
+                "Failed to parse release journal `{}`: {}",
+                path.display(),
+                err
+            ))
+        }
+    };
+
+    journal.path = path.clone();
+
+    Ok(journal)
+}
+
+// Finds the journal of the given version, or the most recently changed
+// unfinished journal if no version is given.
+pub fn find_journal(
+    project_dir: &Path,
+    version_number: Option<String>,
+) -> Result<ReleaseJournal, anyhow::Error> {
+    if let Some(version) = version_number {
+        let path = journal_path(project_dir, &version);
+
+        if !path.exists() {
+            return Err(PeonyError::Config(format!(
+                "Failed to find a release journal for `{}`",
+                version
+            ))
+            .into());
+        }
+
+        return load_journal(&path);
+    }
+
+    let journal_dir = project_dir.join(JOURNAL_DIR);
+
+    let entries = match fs::read_dir(&journal_dir) {
+        Ok(entries) => entries,
+        Err(err) => {
+            return Err(PeonyError::Config(format!(
+                "Failed to read `{}` directory: {}",
+                journal_dir.display(),
+                err
+            ))
+            .into())
+        }
+    };
+
+    let mut latest_journal: Option<(std::time::SystemTime, ReleaseJournal)> = None;
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+
+        if path.extension() != Some(OsStr::new("json")) {
+            continue;
+        }
+
+        let journal = load_journal(&path)?;
+
+        if journal.is_complete() {
+            continue;
+        }
+
+        let modified = match entry.metadata().and_then(|m| m.modified()) {
+            Ok(time) => time,
+            Err(err) => return Err(anyhow!("Failed to read journal metadata: {}", err)),
+        };
+
+        let is_latest = match &latest_journal {
+            Some((latest_modified, _)) => modified > *latest_modified,
+            None => true,
+        };
+
+        if is_latest {
+            latest_journal = Some((modified, journal))
+        }
+    }
+
+    match latest_journal {
+        Some((_, journal)) => Ok(journal),
+        None => Err(PeonyError::Config(
+            "Failed to find an unfinished release to resume".to_owned(),
+        )
+        .into()),
+    }
+}
+
+pub fn verify_artifacts(journal: &ReleaseJournal) -> Result<(), anyhow::Error> {
+    for file in &journal.release.files {
+        if sha512_hash(&file.file_path)? != file.sha512 {
+            return Err(PeonyError::Artifact(format!(
+                "Artifact `{}` has changed since the release was started",
+                file.file_path.display()
+            ))
+            .into());
+        }
+    }
+
+    Ok(())
+}
+
+// The journal itself is kept as a record of the release, only the copied
+// artifacts are removed.
+pub fn finish_journal(journal: &ReleaseJournal, project_dir: &Path) -> Result<(), anyhow::Error> {
+    let artifacts_path = artifacts_dir(project_dir, &journal.release.version_number);
+
+    if !artifacts_path.exists() {
+        return Ok(());
+    }
+
+    match fs::remove_dir_all(&artifacts_path) {
+        Ok(_) => Ok(()),
+        Err(err) => Err(anyhow!("Failed to remove release artifacts: {}", err)),
+    }
+}
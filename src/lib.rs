@@ -0,0 +1,28 @@
+pub mod actions;
+pub mod config;
+pub mod discord;
+pub mod dry_run;
+pub mod error;
+pub mod github;
+pub mod gradle;
+pub mod init;
+pub mod journal;
+pub mod loader_metadata;
+pub mod logging;
+pub mod mc_mod;
+pub mod models;
+pub mod modrinth;
+pub mod output;
+pub mod pack;
+pub mod progress;
+pub mod publisher;
+pub mod release;
+pub mod schema;
+pub mod util;
+pub mod validate;
+pub mod version;
+
+pub use error::PeonyError;
+pub use release::{
+    release_mod, release_modpack, resume_release, ModReleaseOptions, ModpackReleaseOptions,
+};
@@ -0,0 +1,358 @@
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::PathBuf;
+use zip::ZipArchive;
+
+use crate::error::PeonyError;
+use crate::models::modrinth::Loader;
+use crate::models::project_type::mc_mod::fabric::FabricModJson;
+use crate::models::project_type::mc_mod::forge::{ModsToml, FORGE_MODS_TOML, NEOFORGE_MODS_TOML};
+use crate::models::project_type::mc_mod::quilt::QuiltModJson;
+use crate::models::project_type::mc_mod::{ModDependency, ModInfo};
+use crate::util::file_exists_in_zip;
+
+pub const FABRIC_MOD_JSON: &str = "fabric.mod.json";
+pub const QUILT_MOD_JSON: &str = "quilt.mod.json";
+const MANIFEST_FILE: &str = "META-INF/MANIFEST.MF";
+
+// Quilt can load Fabric mods, so a jar with both files is a Quilt mod that
+// stays compatible with Fabric
+pub const METADATA_FILES: [(&str, Loader); 4] = [
+    (QUILT_MOD_JSON, Loader::Quilt),
+    (FABRIC_MOD_JSON, Loader::Fabric),
+    (NEOFORGE_MODS_TOML, Loader::Neoforge),
+    (FORGE_MODS_TOML, Loader::Forge),
+];
+
+// Reads the mod's info from the loader metadata in its jar
+pub fn get_mod_info(jar_path: &PathBuf) -> Result<ModInfo, anyhow::Error> {
+    let jar_file = File::open(jar_path)?;
+
+    let mut archive = ZipArchive::new(jar_file)?;
+
+    let (file_name, loader) = match METADATA_FILES
+        .iter()
+        .find(|(file_name, _)| file_exists_in_zip(&mut archive, file_name))
+    {
+        Some(metadata_file) => *metadata_file,
+        None => {
+            return Err(PeonyError::Artifact(format!(
+                "Failed to get `{}`, `{}`, `{}` or `{}` from jar",
+                FABRIC_MOD_JSON, QUILT_MOD_JSON, NEOFORGE_MODS_TOML, FORGE_MODS_TOML
+            ))
+            .into())
+        }
+    };
+
+    let metadata_string = read_zip_file(&mut archive, file_name)?;
+
+    let mut mod_info = parse_loader_metadata(&metadata_string, file_name, loader)?;
+
+    if mod_info.version == "${file.jarVersion}" {
+        mod_info.version = match get_manifest_version(&mut archive)? {
+            Some(version) => version,
+            None => {
+                return Err(PeonyError::Artifact(format!(
+                    "`{}` uses `${{file.jarVersion}}`, but the jar manifest has no \
+                     `Implementation-Version`",
+                    file_name
+                ))
+                .into())
+            }
+        }
+    }
+
+    Ok(mod_info)
+}
+
+// Also used on the unbuilt metadata in `src/main/resources`, so values can
+// still contain Gradle placeholders like `${version}`
+pub fn parse_loader_metadata(
+    metadata_string: &str,
+    file_name: &str,
+    loader: Loader,
+) -> Result<ModInfo, anyhow::Error> {
+    let mod_info = match loader {
+        Loader::Fabric => serde_json::from_str::<FabricModJson>(metadata_string)
+            .map(fabric_mod_info)
+            .map_err(|err| err.to_string()),
+        Loader::Quilt => serde_json::from_str::<QuiltModJson>(metadata_string)
+            .map(quilt_mod_info)
+            .map_err(|err| err.to_string()),
+        _ => toml::from_str::<ModsToml>(metadata_string)
+            .map_err(|err| err.to_string())
+            .and_then(|mods_toml| mods_toml_info(mods_toml, file_name, loader)),
+    };
+
+    match mod_info {
+        Ok(mod_info) => Ok(mod_info),
+        Err(err) => {
+            Err(PeonyError::Artifact(format!("Failed to parse `{}`: {}", file_name, err)).into())
+        }
+    }
+}
+
+fn fabric_mod_info(mod_json: FabricModJson) -> ModInfo {
+    ModInfo {
+        name: mod_json.name.unwrap_or(mod_json.id.clone()),
+        id: mod_json.id,
+        version: mod_json.version,
+        loader: Loader::Fabric,
+        description: mod_json.description,
+        authors: mod_json
+            .authors
+            .iter()
+            .map(|author| author.name().to_owned())
+            .collect(),
+        licenses: mod_json
+            .license
+            .map(|license| license.into_vec())
+            .unwrap_or_default(),
+        contact: mod_json.contact,
+        dependencies: mod_json
+            .depends
+            .into_iter()
+            .map(|(id, versions)| ModDependency {
+                id,
+                version_ranges: versions.into_vec(),
+            })
+            .collect(),
+    }
+}
+
+fn quilt_mod_info(mod_json: QuiltModJson) -> ModInfo {
+    let loader = mod_json.quilt_loader;
+    let metadata = loader.metadata;
+
+    ModInfo {
+        name: metadata.name.unwrap_or(loader.id.clone()),
+        id: loader.id,
+        version: loader.version,
+        loader: Loader::Quilt,
+        description: metadata.description,
+        authors: metadata.contributors.into_keys().collect(),
+        licenses: metadata
+            .license
+            .map(|license| {
+                license
+                    .into_vec()
+                    .iter()
+                    .map(|license| license.id().to_owned())
+                    .collect()
+            })
+            .unwrap_or_default(),
+        contact: metadata.contact,
+        dependencies: loader
+            .depends
+            .iter()
+            .flat_map(|dep| dep.flatten())
+            .filter_map(|dep| {
+                dep.id().map(|id| ModDependency {
+                    id: id.to_owned(),
+                    version_ranges: dep.version_ranges(),
+                })
+            })
+            .collect(),
+    }
+}
+
+fn mods_toml_info(mods_toml: ModsToml, file_name: &str, loader: Loader) -> Result<ModInfo, String> {
+    // Jars can contain several mods, the first one is the main mod
+    let main_mod = match mods_toml.mods.first() {
+        Some(main_mod) => main_mod.clone(),
+        None => return Err(format!("Found no `[[mods]]` in `{}`", file_name)),
+    };
+
+    let loader = match loader {
+        Loader::Forge if mods_toml.depends_on_neoforge() => Loader::Neoforge,
+        loader => loader,
+    };
+
+    let mut contact = BTreeMap::new();
+
+    if let Some(url) = main_mod.display_url {
+        contact.insert("homepage".to_owned(), url);
+    }
+
+    if let Some(url) = mods_toml.issue_tracker_url {
+        contact.insert("issues".to_owned(), url);
+    }
+
+    Ok(ModInfo {
+        name: main_mod.display_name.unwrap_or(main_mod.mod_id.clone()),
+        // Forge defaults a missing version to `1`
+        version: main_mod.version.unwrap_or("1".to_owned()),
+        loader,
+        description: main_mod
+            .description
+            .map(|description| description.trim().to_owned()),
+        authors: main_mod
+            .authors
+            .map(|authors| vec![authors])
+            .unwrap_or_default(),
+        licenses: mods_toml.license.into_iter().collect(),
+        contact,
+        dependencies: mods_toml
+            .dependencies
+            .get(&main_mod.mod_id)
+            .into_iter()
+            .flatten()
+            .map(|dep| ModDependency {
+                id: dep.mod_id.clone(),
+                version_ranges: dep.version_range.clone().into_iter().collect(),
+            })
+            .collect(),
+        id: main_mod.mod_id,
+    })
+}
+
+fn get_manifest_version(archive: &mut ZipArchive<File>) -> Result<Option<String>, anyhow::Error> {
+    if !file_exists_in_zip(archive, MANIFEST_FILE) {
+        return Ok(None);
+    }
+
+    Ok(read_zip_file(archive, MANIFEST_FILE)?
+        .lines()
+        .find_map(|line| line.strip_prefix("Implementation-Version:"))
+        .map(|version| version.trim().to_owned()))
+}
+
+fn read_zip_file(archive: &mut ZipArchive<File>, file_name: &str) -> Result<String, anyhow::Error> {
+    let mut file = match archive.by_name(file_name) {
+        Ok(file) => file,
+        Err(err) => {
+            return Err(PeonyError::Artifact(format!(
+                "Failed to get `{}` file from jar: {}",
+                file_name, err
+            ))
+            .into())
+        }
+    };
+
+    let mut file_string = String::new();
+
+    match file.read_to_string(&mut file_string) {
+        Ok(_) => Ok(file_string),
+        Err(err) => Err(PeonyError::Artifact(format!(
+            "Failed to read `{}` from jar: {}",
+            file_name, err
+        ))
+        .into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quilt_any_of_dependencies() {
+        let mod_json = r#"{
+            "quilt_loader": {
+                "id": "example",
+                "version": "1.0.0",
+                "depends": [
+                    "quilt_loader",
+                    { "id": "minecraft", "versions": ">=1.20" },
+                    [
+                        { "id": "quilted_fabric_api", "versions": ">=7.0.0" },
+                        "fabric-api"
+                    ]
+                ]
+            }
+        }"#;
+
+        let mod_info = parse_loader_metadata(mod_json, "quilt.mod.json", Loader::Quilt).unwrap();
+
+        let ids: Vec<&str> = mod_info
+            .dependencies
+            .iter()
+            .map(|dep| dep.id.as_str())
+            .collect();
+
+        assert_eq!(
+            ids,
+            [
+                "quilt_loader",
+                "minecraft",
+                "quilted_fabric_api",
+                "fabric-api"
+            ]
+        );
+        assert_eq!(mod_info.dependencies[2].version_ranges, [">=7.0.0"]);
+    }
+
+    #[test]
+    fn mods_toml_main_mod() {
+        let mods_toml = r#"
+license = "MIT"
+issueTrackerURL = "https://example.com/issues"
+
+[[mods]]
+modId = "example"
+version = "1.0.0"
+displayName = "Example Mod"
+description = """
+  An example mod
+"""
+authors = "Someone"
+
+[[mods]]
+modId = "example_extra"
+
+[[dependencies.example]]
+modId = "minecraft"
+versionRange = "[1.20.1,1.21)"
+
+[[dependencies.example_extra]]
+modId = "jei"
+"#;
+
+        let mod_info = parse_loader_metadata(mods_toml, "mods.toml", Loader::Forge).unwrap();
+
+        assert_eq!(mod_info.id, "example");
+        assert_eq!(mod_info.name, "Example Mod");
+        assert_eq!(mod_info.version, "1.0.0");
+        assert_eq!(mod_info.loader, Loader::Forge);
+        assert_eq!(mod_info.description.as_deref(), Some("An example mod"));
+        assert_eq!(mod_info.authors, ["Someone"]);
+        assert_eq!(mod_info.licenses, ["MIT"]);
+        assert_eq!(
+            mod_info.contact.get("issues").map(String::as_str),
+            Some("https://example.com/issues")
+        );
+        assert_eq!(mod_info.dependencies.len(), 1);
+        assert_eq!(mod_info.dependencies[0].id, "minecraft");
+        assert_eq!(mod_info.dependencies[0].version_ranges, ["[1.20.1,1.21)"]);
+    }
+
+    #[test]
+    fn mods_toml_defaults_and_neoforge() {
+        let mods_toml = r#"
+[[mods]]
+modId = "example"
+
+[[dependencies.example]]
+modId = "neoforge"
+"#;
+
+        let mod_info = parse_loader_metadata(mods_toml, "mods.toml", Loader::Forge).unwrap();
+
+        assert_eq!(mod_info.name, "example");
+        assert_eq!(mod_info.version, "1");
+        assert_eq!(mod_info.loader, Loader::Neoforge);
+    }
+
+    #[test]
+    fn mods_toml_without_mods() {
+        let err =
+            parse_loader_metadata("license = \"MIT\"\nmods = []\n", "mods.toml", Loader::Forge)
+                .unwrap_err();
+
+        assert!(matches!(
+            err.downcast_ref::<PeonyError>(),
+            Some(PeonyError::Artifact(_))
+        ));
+    }
+}
@@ -0,0 +1,53 @@
+use clap::ValueEnum;
+use std::cmp::min;
+use tracing::level_filters::LevelFilter;
+use tracing_subscriber::filter::Targets;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+
+use crate::progress::set_progress_hidden;
+
+#[derive(Debug, Copy, Clone, ValueEnum)]
+pub enum LogFormat {
+    Text,
+    Json,
+}
+
+// Logs go to stderr, so stdout only has the command's output (e.g. the
+// `--output json` summary).
+pub fn init_logging(verbose: u8, quiet: u8, log_format: LogFormat) {
+    let level = match verbose as i16 - quiet as i16 {
+        ..=-2 => LevelFilter::ERROR,
+        -1 => LevelFilter::WARN,
+        0 => LevelFilter::INFO,
+        1 => LevelFilter::DEBUG,
+        _ => LevelFilter::TRACE,
+    };
+
+    // Dependencies like serenity log a lot, so only their warnings are shown
+    let filter = Targets::new()
+        .with_target(env!("CARGO_CRATE_NAME"), level)
+        .with_default(min(level, LevelFilter::WARN));
+
+    set_progress_hidden(level < LevelFilter::INFO || matches!(log_format, LogFormat::Json));
+
+    let registry = tracing_subscriber::registry().with(filter);
+
+    match log_format {
+        LogFormat::Text => registry
+            .with(
+                tracing_subscriber::fmt::layer()
+                    .with_writer(std::io::stderr)
+                    .with_target(false)
+                    .without_time(),
+            )
+            .init(),
+        LogFormat::Json => registry
+            .with(
+                tracing_subscriber::fmt::layer()
+                    .json()
+                    .with_writer(std::io::stderr),
+            )
+            .init(),
+    }
+}
@@ -1,41 +1,37 @@
-use std::io::Read;
-use std::{env, fs};
-
-use anyhow::anyhow;
-use clap::{command, Parser, Subcommand};
-use glob::glob;
-use std::path::{Path, PathBuf};
-use std::process::Command;
-
-use crate::discord::send_discord_webhook;
-use crate::models::project_type::mc_mod::version::ModVersionInfo;
-use crate::models::project_type::mc_mod::{Jar, ModInfo, ModJars};
-use crate::{
-    github::generate_changelog,
-    models::{
-        modrinth::ModrinthUrl,
-        project_type::{mc_mod::config::ModConfig, modpack::config::ModpackConfig},
-    },
-    pack::*,
-    util::*,
-    version::*,
+use clap::{Parser, Subcommand};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use peony::actions::annotate_error;
+use peony::config::migrate_project_configs;
+use peony::error::{exit_code, PeonyError};
+use peony::init::init_project;
+use peony::logging::{init_logging, LogFormat};
+use peony::models::modrinth::version::VersionType;
+use peony::models::target::{Target, Targets};
+use peony::output::{write_summary, OutputFormat};
+use peony::release::{
+    release_mod, release_modpack, resume_release, ModReleaseOptions, ModpackReleaseOptions,
 };
-use crate::models::modrinth::version::VersionType;
-
-mod discord;
-mod github;
-mod mc_mod;
-mod models;
-mod modrinth;
-mod pack;
-mod util;
-mod version;
+use peony::schema::{print_schema, SchemaType};
+use peony::validate::{validate_project, ValidationReport};
 
 #[derive(Debug, Parser)]
 #[command(name = "peony", author, version, about)]
 struct CliArgs {
     #[command(subcommand)]
     commands: Commands,
+    #[clap(long, short, action = clap::ArgAction::Count, help = "Log more, can be repeated")]
+    verbose: u8,
+    #[clap(long, short, action = clap::ArgAction::Count, help = "Log less, can be repeated")]
+    quiet: u8,
+    #[clap(
+        long,
+        value_enum,
+        default_value = "text",
+        help = "Format of log messages"
+    )]
+    log_format: LogFormat,
 }
 
 #[derive(Debug, Subcommand)]
@@ -46,436 +42,275 @@ enum Commands {
         discord: bool,
         #[clap(long, short, help = "Custom version number")]
         version: Option<String>,
-        #[clap(long, short = 'V', help = "Version type (used for Modrinth & GitHub releases)")]
-        version_type: Option<VersionType>
+        #[clap(
+            long,
+            short = 'V',
+            help = "Version type (used for Modrinth & GitHub releases)"
+        )]
+        version_type: Option<VersionType>,
+        #[clap(long, help = "Print release requests instead of publishing them")]
+        dry_run: bool,
+        #[clap(
+            long,
+            value_delimiter = ',',
+            conflicts_with = "skip",
+            help = "Only release to these targets"
+        )]
+        only: Vec<Target>,
+        #[clap(long, value_delimiter = ',', help = "Skip releasing to these targets")]
+        skip: Vec<Target>,
+        #[clap(
+            long,
+            help = "Path to the config file (defaults to `mrpack.toml` in the project directory)"
+        )]
+        config: Option<PathBuf>,
+        #[clap(long, help = "Directory of the project", default_value = ".")]
+        project_dir: PathBuf,
+        #[clap(
+            long,
+            help = "Config profile to apply, from the config's `[profile.<name>]` tables"
+        )]
+        profile: Option<String>,
+        #[clap(
+            long,
+            value_enum,
+            default_value = "text",
+            help = "Format of the release summary"
+        )]
+        output: OutputFormat,
+        #[clap(long, help = "Also write the release summary as JSON to this file")]
+        output_file: Option<PathBuf>,
     },
-    #[command(about = "Build and upload a Fabric/Quilt mod")]
+    #[command(about = "Build and upload a Fabric, Quilt, Forge or NeoForge mod")]
     Mod {
         #[clap(long, short, help = "Whether or not to send Discord webhook")]
         discord: bool,
-        #[clap(long, short, help = "Args to pass to Gradle", default_value = "build")]
-        gradle_args: String,
-        #[clap(long, short = 'V', help = "Version type (used for Modrinth & GitHub releases)")]
-        version_type: Option<VersionType>
+        #[clap(
+            long,
+            short = 'g',
+            alias = "gradle-args",
+            value_delimiter = ' ',
+            help = "Gradle tasks to run, separated by spaces (defaults to the config's, or `build`)"
+        )]
+        gradle_tasks: Vec<String>,
+        #[clap(
+            long = "gradle-property",
+            short = 'P',
+            value_name = "KEY=VALUE",
+            value_parser = parse_property,
+            help = "Property to pass to Gradle, can be repeated"
+        )]
+        gradle_properties: Vec<(String, String)>,
+        #[clap(
+            long,
+            value_name = "SECONDS",
+            help = "Fail the build if Gradle takes longer"
+        )]
+        gradle_timeout: Option<u64>,
+        #[clap(
+            long,
+            short = 'V',
+            help = "Version type (used for Modrinth & GitHub releases)"
+        )]
+        version_type: Option<VersionType>,
+        #[clap(long, help = "Print release requests instead of publishing them")]
+        dry_run: bool,
+        #[clap(
+            long,
+            value_delimiter = ',',
+            conflicts_with = "skip",
+            help = "Only release to these targets"
+        )]
+        only: Vec<Target>,
+        #[clap(long, value_delimiter = ',', help = "Skip releasing to these targets")]
+        skip: Vec<Target>,
+        #[clap(
+            long,
+            help = "Path to the config file (defaults to `peony_mod.toml` in the project directory)"
+        )]
+        config: Option<PathBuf>,
+        #[clap(long, help = "Directory of the project", default_value = ".")]
+        project_dir: PathBuf,
+        #[clap(
+            long,
+            help = "Config profile to apply, from the config's `[profile.<name>]` tables"
+        )]
+        profile: Option<String>,
+        #[clap(
+            long,
+            value_enum,
+            default_value = "text",
+            help = "Format of the release summary"
+        )]
+        output: OutputFormat,
+        #[clap(long, help = "Also write the release summary as JSON to this file")]
+        output_file: Option<PathBuf>,
+    },
+    #[command(about = "Create a Peony config for the project in the current directory")]
+    Init {
+        #[clap(long, short, help = "Use detected values without prompting")]
+        yes: bool,
+        #[clap(long, short, help = "Overwrite an existing config file")]
+        force: bool,
+    },
+    #[command(about = "Continue an unfinished release without rebuilding it")]
+    Resume {
+        #[clap(
+            long,
+            short,
+            help = "Version to resume (defaults to the latest unfinished release)"
+        )]
+        version: Option<String>,
+        #[clap(long, help = "Print release requests instead of publishing them")]
+        dry_run: bool,
+        #[clap(long, help = "Directory of the project", default_value = ".")]
+        project_dir: PathBuf,
+        #[clap(
+            long,
+            value_enum,
+            default_value = "text",
+            help = "Format of the release summary"
+        )]
+        output: OutputFormat,
+        #[clap(long, help = "Also write the release summary as JSON to this file")]
+        output_file: Option<PathBuf>,
+    },
+    #[command(about = "Check the project config for problems")]
+    Validate,
+    #[command(about = "Update a config file to the latest config format version")]
+    MigrateConfig {
+        #[clap(
+            long,
+            help = "Path to the config file (defaults to every config in the current directory)"
+        )]
+        config: Option<PathBuf>,
+    },
+    #[command(about = "Print the JSON Schema of a config file")]
+    Schema {
+        #[clap(value_enum, help = "Project type of the config")]
+        schema_type: SchemaType,
     },
 }
 
 #[tokio::main]
-async fn main() -> Result<(), anyhow::Error> {
+async fn main() -> ExitCode {
     let _ = dotenvy::dotenv();
 
     let args = CliArgs::parse();
 
-    match args.commands {
-        Commands::Modpack { discord, version, version_type } => {
-            match which::which("packwiz") {
-                Ok(_) => (),
-                Err(err) => return Err(anyhow!("Failed to find packwiz executable: {}", err)),
-            }
-
-            if !Path::new("mrpack.toml").exists() {
-                return Err(anyhow!("Failed to find `mrpack.toml` file."));
-            }
-
-            let config_file = match fs::read_to_string("mrpack.toml") {
-                Ok(content_string) => {
-                    let parsed_config: ModpackConfig = match toml::from_str(&content_string) {
-                        Ok(config) => config,
-                        Err(err) => return Err(anyhow!("Failed to parse config file: {}", err)),
-                    };
-                    parsed_config
-                }
-                Err(err) => return Err(anyhow!("Failed to read config file: {}", err)),
-            };
-
-            let mut pack_file = match get_pack_file() {
-                Ok(file) => file,
-                Err(err) => return Err(err),
-            };
-
-            let tmp_info = match create_temp() {
-                Ok(info) => info,
-                Err(err) => return Err(err),
-            };
-
-            if let Some(ver) = version {
-                let mut new_file_contents = pack_file.clone();
-                new_file_contents.version = ver;
-                let file_contents_string = match toml::to_string(&new_file_contents) {
-                    Ok(file) => file,
-                    Err(err) => {
-                        return Err(anyhow!("Failed to parse new pack data to toml: {}", err))
-                    }
-                };
-
-                pack_file = new_file_contents;
-
-                write_pack_file(&tmp_info.dir_path, file_contents_string)?
-            }
-
-            match Command::new("packwiz")
-                .arg("mr")
-                .arg("export")
-                .current_dir(&tmp_info.dir_path)
-                .output()
-            {
-                Ok(_) => (),
-                Err(err) => return Err(anyhow!("Failed to export with packwiz: {}", err)),
-            }
-
-            let output_file_info = match get_output_file(&tmp_info) {
-                Ok(file_info) => file_info,
-                Err(err) => return Err(err),
-            };
-
-            let version_info =
-                match get_modpack_version_info(&config_file, &pack_file, &output_file_info) {
-                    Ok(info) => info,
-                    Err(err) => return Err(err),
-                };
+    init_logging(args.verbose, args.quiet, args.log_format);
 
-            // Changelog
+    match run(args).await {
+        Ok(_) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("Error: {:#}", err);
+            annotate_error(&format!("{:#}", err));
 
-            let changelog_markdown = match generate_changelog(&config_file.github).await {
-                Ok(changelog) => changelog,
-                Err(err) => return Err(err),
-            };
-
-            // GitHub Release
-
-            match github::create_modpack_release(
-                &config_file,
-                &pack_file,
-                &output_file_info,
-                &version_info,
-                &changelog_markdown,
-                match version_type.clone() {
-                    Some(ver_type) => ver_type,
-                    None => VersionType::Release
-                }
-            )
-            .await
-            {
-                Ok(_) => (),
-                Err(err) => println!("Failed to create GitHub release: {}", err),
-            }
-
-            // Modrinth Release
-
-            let modrinth_token = match env::var("MODRINTH_TOKEN") {
-                Ok(token) => token,
-                Err(err) => return Err(anyhow!("Failed to get `MODRINTH_TOKEN`: {}", err)),
-            };
-
-            let modrinth_url = ModrinthUrl::new(&config_file.modrinth.staging);
-
-            match modrinth::create_modpack_release(
-                &config_file,
-                &pack_file,
-                &output_file_info,
-                &version_info,
-                &changelog_markdown,
-                modrinth_token.clone(),
-                &modrinth_url,
-                match version_type {
-                    Some(ver_type) => ver_type,
-                    None => VersionType::Release
-                }
-            )
-            .await
-            {
-                Ok(_) => (),
-                Err(err) => println!("{}", err),
-            }
-
-            // Send Discord webhook
-
-            if discord {
-                let discord_config = match config_file.discord {
-                    Some(config) => config,
-                    None => return Err(anyhow!("Failed to get Discord config")),
-                };
-
-                match send_discord_webhook(
-                    &discord_config,
-                    &modrinth_url,
-                    &config_file.modrinth.project_id,
-                    &config_file.github,
-                    &version_info.version_name,
-                    &changelog_markdown,
-                )
-                .await
-                {
-                    Ok(_) => {
-                        println!("Sent Discord webhook!")
-                    }
-                    Err(err) => return Err(err),
-                }
-            }
+            ExitCode::from(exit_code(&err))
+        }
+    }
+}
 
-            clean_up(&tmp_info.dir_path)?
+async fn run(args: CliArgs) -> Result<(), anyhow::Error> {
+    match args.commands {
+        Commands::Modpack {
+            discord,
+            version,
+            version_type,
+            dry_run,
+            only,
+            skip,
+            config,
+            project_dir,
+            profile,
+            output,
+            output_file,
+        } => {
+            let summary = release_modpack(ModpackReleaseOptions {
+                project_dir,
+                config,
+                profile,
+                version,
+                version_type: version_type.unwrap_or(VersionType::Release),
+                targets: Targets::new(&only, &skip, discord),
+                dry_run,
+            })
+            .await?;
+
+            write_summary(&summary, output, output_file.as_deref())?;
         }
         Commands::Mod {
             discord,
-            gradle_args,
-            version_type
+            gradle_tasks,
+            gradle_properties,
+            gradle_timeout,
+            version_type,
+            dry_run,
+            only,
+            skip,
+            config,
+            project_dir,
+            profile,
+            output,
+            output_file,
         } => {
-            match which::which("java") {
-                Ok(_) => (),
-                Err(err) => return Err(anyhow!("Failed to find Java executable: {}", err)),
-            }
-
-            let gradlew_path: &Path = if env::consts::OS == "windows" {
-                Path::new(".\\gradlew.bat")
-            } else {
-                Path::new("./gradlew")
-            };
-
-            if !Path::new(gradlew_path).exists() {
-                return Err(anyhow!(
-                    "Failed to find gradle script at `{:?}`",
-                    gradlew_path
-                ));
-            }
-
-            if !Path::new("peony_mod.toml").exists() {
-                return Err(anyhow!("Failed to find `peony_mod.toml` file"));
-            }
-
-            let config_file = match fs::read_to_string("peony_mod.toml") {
-                Ok(content_string) => {
-                    let parsed_config: ModConfig = match toml::from_str(&content_string) {
-                        Ok(config) => config,
-                        Err(err) => return Err(anyhow!("Failed to parse config file: {}", err)),
-                    };
-                    parsed_config
-                }
-                Err(err) => return Err(anyhow!("Failed to read config file: {}", err)),
-            };
-
-            let tmp_info = match create_temp() {
-                Ok(info) => info,
-                Err(err) => return Err(anyhow!("Failed to create temporary directory: {}", err)),
-            };
-
-            // remove previously-compiled jars, if any
-            let _ = fs::remove_dir(&tmp_info.dir_path.join("build").join("libs"));
-
-            let mut gradle_command = Command::new(gradlew_path);
-
-            let gradle_command = gradle_command
-                .arg(gradle_args)
-                .current_dir(&tmp_info.dir_path);
-
-            let mut gradle_child = match gradle_command.spawn() {
-                Ok(child) => child,
-                Err(err) => return Err(anyhow!("Failed to build with Gradle: {}", err)),
-            };
-
-            gradle_child.wait().unwrap();
-
-            let jars = match glob(
-                match Path::new(&tmp_info.dir_path)
-                    .join("build")
-                    .join("libs")
-                    .join("*.jar")
-                    .to_str()
-                {
-                    Some(path) => path,
-                    None => return Err(anyhow!("Failed to parse glob to string")),
-                },
-            ) {
-                Ok(paths) => paths,
-                Err(err) => return Err(anyhow!("Failed to find files with mod glob: {}", err)),
-            };
-
-            let mut possible_jars: Vec<PathBuf> = vec![];
-            let mut possible_sources_jars: Vec<PathBuf> = vec![];
-
-            for jar in jars {
-                let jar_path = match jar {
-                    Ok(path) => path,
-                    Err(err) => {
-                        return Err(anyhow!("Failed to parse jar glob result as path: {}", err))
-                    }
-                };
-
-                let file_name = match jar_path.file_name() {
-                    Some(name) => match name.to_os_string().into_string() {
-                        Ok(name) => name,
-                        // better error message needed, but fine for now
-                        Err(_) => {
-                            return Err(anyhow!(
-                                "Failed to parse file name from OsString to String"
-                            ))
-                        }
-                    },
-                    None => return Err(anyhow!("Failed to parse file name from jar path")),
-                };
-
-                if file_name.ends_with("-sources.jar") {
-                    possible_sources_jars.push(jar_path)
-                } else if file_name.ends_with(".jar") {
-                    possible_jars.push(jar_path)
-                }
-            }
-
-            if possible_jars.len() != 1 {
-                clean_up(&tmp_info.dir_path)?;
-                return Err(anyhow!(
-                    "Found an invalid amount of mod jars: {}",
-                    possible_jars.len()
-                ));
-            }
-
-            let jar_path = &possible_jars[0];
-            let sources_jar_path: Option<&PathBuf> = match possible_sources_jars.len() {
-                0 => None,
-                1 => Some(&possible_sources_jars[0]),
-                _ => {
-                    return Err(anyhow!(
-                        "Found an invalid amount of sources jars: {}",
-                        possible_sources_jars.len()
-                    ))
-                }
-            };
-
-            let jar_file = fs::File::open(jar_path)?;
-
-            let mut archive = zip::ZipArchive::new(jar_file)?;
-
-            let mut loader_file = if file_exists_in_zip(&mut archive, "fabric.mod.json") {
-                match archive.by_name("fabric.mod.json") {
-                    Ok(file) => file,
-                    Err(err) => {
-                        return Err(anyhow!(
-                            "Failed to get `fabric.mod.json` file from jar: {}",
-                            err
-                        ))
-                    }
-                }
-            } else if file_exists_in_zip(&mut archive, "quilt.mod.json") {
-                match archive.by_name("quilt.mod.json") {
-                    Ok(file) => file,
-                    Err(err) => {
-                        return Err(anyhow!(
-                            "Failed to get `quilt.mod.json` file from jar: {}",
-                            err
-                        ))
-                    }
-                }
-            } else {
-                return Err(anyhow!(
-                    "Failed to get `fabric.mod.json` or `quilt.mod.json` from jar"
-                ));
-            };
-
-            let mut loader_file_string = String::new();
-            loader_file.read_to_string(&mut loader_file_string)?;
-
-            let parsed_loader_file: serde_json::Value = serde_json::from_str(&loader_file_string)?;
-
-            let mod_info = ModInfo {
-                name: trim_quotes(parsed_loader_file["name"].to_string()),
-                id: trim_quotes(parsed_loader_file["id"].to_string()),
-                version: trim_quotes(parsed_loader_file["version"].to_string()),
-            };
-
-            let mod_jar_name = file_name_from_path(jar_path)?;
-
-            let sources_jar_name = match sources_jar_path {
-                Some(path) => Some(file_name_from_path(path)?),
-                None => None,
-            };
-
-            let mod_jar = Jar {
-                file_name: mod_jar_name,
-                file_path: jar_path.into(),
-            };
-
-            let sources_jar = sources_jar_name.map(|name| Jar {
-                file_name: name,
-                file_path: sources_jar_path.unwrap().into(),
-            });
-
-            let mod_jars = ModJars {
-                mod_jar,
-                sources_jar,
-            };
-
-            let version_info = ModVersionInfo::new(&config_file, &mod_jars, &mod_info)?;
-
-            // Generate changelog from previous GitHub Releases
-            let changelog_markdown = match generate_changelog(&config_file.github).await {
-                Ok(changelog) => changelog,
-                Err(err) => return Err(err),
-            };
-
-            // Create GitHub Release
-
-            match github::create_mod_release(
-                &config_file,
-                &mod_info,
-                &mod_jars,
-                &changelog_markdown,
-                &version_info.name,
-                match version_type.clone() {
-                    Some(ver_type) => ver_type,
-                    None => VersionType::Release
-                }
-            )
-            .await
-            {
-                Ok(_) => {}
-                Err(err) => return Err(err),
-            };
+            let summary = release_mod(ModReleaseOptions {
+                project_dir,
+                config,
+                profile,
+                gradle_tasks,
+                gradle_properties,
+                gradle_timeout,
+                version_type: version_type.unwrap_or(VersionType::Release),
+                targets: Targets::new(&only, &skip, discord),
+                dry_run,
+            })
+            .await?;
+
+            write_summary(&summary, output, output_file.as_deref())?;
+        }
+        Commands::Init { yes, force } => {
+            let config_path = init_project(!yes, force)?;
+            println!("Created `{}`!", config_path);
+        }
+        Commands::Validate => print_validation(validate_project()?)?,
+        Commands::Schema { schema_type } => print_schema(schema_type)?,
+        Commands::MigrateConfig { config } => migrate_project_configs(config)?,
+        Commands::Resume {
+            version,
+            dry_run,
+            project_dir,
+            output,
+            output_file,
+        } => {
+            let summary = resume_release(&project_dir, version, dry_run).await?;
 
-            // Create Modrinth Release
+            write_summary(&summary, output, output_file.as_deref())?;
+        }
+    }
+    Ok(())
+}
 
-            let modrinth_url = ModrinthUrl::new(&config_file.modrinth.staging);
+fn print_validation(reports: Vec<ValidationReport>) -> Result<(), anyhow::Error> {
+    let mut error_count = 0;
 
-            match modrinth::create_mod_release(
-                &config_file,
-                &version_info,
-                &changelog_markdown,
-                &modrinth_url,
-                &version_info.name,
-                match version_type {
-                    Some(ver_type) => ver_type,
-                    None => VersionType::Release
-                }
-            )
-            .await
-            {
-                Ok(_) => {}
-                Err(err) => return Err(err),
-            };
+    for report in &reports {
+        report.print();
+        error_count += report.errors.len();
+    }
 
-            if discord {
-                let discord_config = match config_file.discord {
-                    Some(config) => config,
-                    None => return Err(anyhow!("Failed to get Discord config")),
-                };
+    if error_count > 0 {
+        return Err(
+            PeonyError::Config(format!("Found {} problem(s) in config", error_count)).into(),
+        );
+    }
 
-                match send_discord_webhook(
-                    &discord_config,
-                    &modrinth_url,
-                    &config_file.modrinth.project_id,
-                    &config_file.github,
-                    &version_info.name,
-                    &changelog_markdown,
-                )
-                .await
-                {
-                    Ok(_) => {
-                        println!("Sent Discord webhook!")
-                    }
-                    Err(err) => return Err(err),
-                }
-            }
+    Ok(())
+}
 
-            clean_up(&tmp_info.dir_path)?
-        }
+fn parse_property(property: &str) -> Result<(String, String), String> {
+    match property.split_once('=') {
+        Some((key, value)) if !key.is_empty() => Ok((key.to_owned(), value.to_owned())),
+        _ => Err(format!("`{}` isn't in the form `KEY=VALUE`", property)),
     }
-    Ok(())
 }
@@ -1,26 +1,318 @@
-// use std::fs::File;
-// use anyhow::anyhow;
-// use zip::{ZipArchive, read::ZipFile};
-// use crate::util::file_exists_in_zip;
-//
-// pub fn get_loader_file(archive: &mut ZipArchive<File>) -> Result<ZipFile, anyhow::Error> {
-//     if file_exists_in_zip(&mut archive, "fabric.mod.json") {
-//         match archive.by_name("fabric.mod.json") {
-//             Ok(file) => Ok(file),
-//             Err(err) => return Err(anyhow!(
-//                 "Failed to get `fabric.mod.json` file from jar: {}", err
-//             ))
-//         }
-//     } else if file_exists_in_zip(&mut archive, "quilt.mod.json") {
-//         match archive.by_name("quilt.mod.json") {
-//             Ok(file) => Some(file),
-//             Err(err) => return Err(anyhow!(
-//                 "Failed to get `quilt.mod.json` file from jar: {}", err
-//             ))
-//         }
-//     } else {
-//         return Err(anyhow!(
-//             "Failed to get `fabric.mod.json` or `quilt.mod.json` from jar"
-//         ))
-//     }
-// }
+use anyhow::anyhow;
+use glob::{glob, Pattern};
+use std::path::{Path, PathBuf};
+
+use crate::error::PeonyError;
+use crate::loader_metadata::get_mod_info;
+use crate::models::modrinth::Loader;
+use crate::models::project_type::mc_mod::config::{ArtifactConfig, ModConfig, SubprojectConfig};
+use crate::models::project_type::mc_mod::version::ModVersionInfo;
+use crate::models::project_type::mc_mod::{Jar, ModBuild, ModJars};
+use crate::util::file_name_from_path;
+
+// Finds the mod jar, its sources jar and any extra files with the
+// configured artifact globs
+pub fn get_mod_jars(
+    project_dir: &Path,
+    artifacts: &ArtifactConfig,
+) -> Result<ModJars, anyhow::Error> {
+    let mut exclude_patterns = vec![];
+
+    for exclude in artifacts.exclude.iter().flatten() {
+        match Pattern::new(exclude) {
+            Ok(pattern) => exclude_patterns.push(pattern),
+            Err(err) => {
+                return Err(PeonyError::Config(format!(
+                    "Invalid artifact exclude glob `{}`: {}",
+                    exclude, err
+                ))
+                .into())
+            }
+        }
+    }
+
+    let sources_jars = find_artifacts(project_dir, artifacts.sources(), &exclude_patterns)?;
+
+    let mut extra_files: Vec<Jar> = vec![];
+
+    for extra in artifacts.extra.iter().flatten() {
+        for file in find_artifacts(project_dir, extra, &exclude_patterns)? {
+            let is_duplicate = sources_jars.iter().any(|s| s.file_path == file.file_path)
+                || extra_files.iter().any(|f| f.file_path == file.file_path);
+
+            if !is_duplicate {
+                extra_files.push(file)
+            }
+        }
+    }
+
+    // The default primary glob matches every jar, so sources jars and extra
+    // files are left out of it
+    let mod_jars: Vec<Jar> = find_artifacts(project_dir, artifacts.primary(), &exclude_patterns)?
+        .into_iter()
+        .filter(|jar| {
+            !sources_jars
+                .iter()
+                .chain(extra_files.iter())
+                .any(|f| f.file_path == jar.file_path)
+        })
+        .collect();
+
+    if mod_jars.len() != 1 {
+        return Err(PeonyError::Artifact(format!(
+            "Found an invalid amount of mod jars matching `{}`: {}{}",
+            artifacts.primary(),
+            mod_jars.len(),
+            if mod_jars.is_empty() {
+                String::new()
+            } else {
+                format!(
+                    " ({}), exclude the others with `artifacts.exclude` in the config",
+                    mod_jars
+                        .iter()
+                        .map(|jar| jar.file_name.as_str())
+                        .collect::<Vec<&str>>()
+                        .join(", ")
+                )
+            }
+        ))
+        .into());
+    }
+
+    let sources_jar = match sources_jars.len() {
+        0 => None,
+        1 => Some(sources_jars[0].clone()),
+        _ => {
+            return Err(PeonyError::Artifact(format!(
+                "Found an invalid amount of sources jars matching `{}`: {}",
+                artifacts.sources(),
+                sources_jars.len()
+            ))
+            .into())
+        }
+    };
+
+    Ok(ModJars {
+        mod_jar: mod_jars[0].clone(),
+        sources_jar,
+        extra_files,
+    })
+}
+
+fn find_artifacts(
+    project_dir: &Path,
+    pattern: &str,
+    exclude_patterns: &[Pattern],
+) -> Result<Vec<Jar>, anyhow::Error> {
+    let paths = match glob(match project_dir.join(pattern).to_str() {
+        Some(path) => path,
+        None => return Err(anyhow!("Failed to parse glob to string")),
+    }) {
+        Ok(paths) => paths,
+        Err(err) => {
+            return Err(
+                PeonyError::Config(format!("Invalid artifact glob `{}`: {}", pattern, err)).into(),
+            )
+        }
+    };
+
+    let mut artifacts = vec![];
+
+    for path in paths {
+        let file_path = match path {
+            Ok(path) => path,
+            Err(err) => {
+                return Err(anyhow!(
+                    "Failed to parse artifact glob result as path: {}",
+                    err
+                ))
+            }
+        };
+
+        if !file_path.is_file() {
+            continue;
+        }
+
+        let file_name = file_name_from_path(&file_path)?;
+
+        if exclude_patterns
+            .iter()
+            .any(|exclude| exclude.matches(&file_name))
+        {
+            continue;
+        }
+
+        artifacts.push(Jar {
+            file_name,
+            file_path,
+        })
+    }
+
+    Ok(artifacts)
+}
+
+// Without a `[[subproject]]` config, directories named after a loader with
+// their own Gradle build script are used, e.g. `fabric` and `neoforge`.
+pub fn get_subprojects(project_dir: &Path, config: &ModConfig) -> Vec<SubprojectConfig> {
+    if let Some(subprojects) = &config.subprojects {
+        return subprojects.clone();
+    }
+
+    [
+        Loader::Fabric,
+        Loader::Quilt,
+        Loader::Neoforge,
+        Loader::Forge,
+    ]
+    .iter()
+    .map(|loader| PathBuf::from(loader.formatted().to_lowercase()))
+    .filter(|dir| {
+        ["build.gradle", "build.gradle.kts"]
+            .iter()
+            .any(|script| project_dir.join(dir).join(script).exists())
+    })
+    .map(|dir| SubprojectConfig {
+        path: dir,
+        loaders: None,
+        mc_versions: None,
+        dependencies: None,
+    })
+    .collect()
+}
+
+// Finds the jars of every loader subproject, or of the project itself if it
+// has no subprojects
+pub fn get_mod_builds(
+    project_dir: &Path,
+    config: &ModConfig,
+) -> Result<Vec<ModBuild>, anyhow::Error> {
+    let subprojects = get_subprojects(project_dir, config);
+    let artifacts = config.artifacts.clone().unwrap_or_default();
+
+    if subprojects.is_empty() {
+        let jars = get_mod_jars(project_dir, &artifacts)?;
+        let mod_info = get_mod_info(&jars.mod_jar.file_path)?;
+        let version_info = ModVersionInfo::new(config, &mod_info, &config.loaders)?;
+
+        return Ok(vec![ModBuild {
+            jars,
+            mod_info,
+            version_info,
+            subproject: None,
+        }]);
+    }
+
+    let mut builds = vec![];
+
+    for subproject in subprojects {
+        let jars = match get_mod_jars(&project_dir.join(&subproject.path), &artifacts) {
+            Ok(jars) => jars,
+            Err(err) => {
+                return Err(err.context(format!(
+                    "Failed to find jars of subproject `{}`",
+                    subproject.path.display()
+                )))
+            }
+        };
+
+        let mod_info = get_mod_info(&jars.mod_jar.file_path)?;
+        let loaders = subproject.loaders.clone().unwrap_or(vec![mod_info.loader]);
+        let version_info = ModVersionInfo::new(config, &mod_info, &loaders)?;
+
+        builds.push(ModBuild {
+            jars,
+            mod_info,
+            version_info,
+            subproject: Some(subproject),
+        })
+    }
+
+    Ok(builds)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn test_project(name: &str, jars: &[&str]) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("peony-jars-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("build").join("libs")).unwrap();
+
+        for jar in jars {
+            fs::write(dir.join("build").join("libs").join(jar), "").unwrap();
+        }
+
+        dir
+    }
+
+    #[test]
+    fn separates_sources_and_extra_files() {
+        let dir = test_project(
+            "default",
+            &["mod-1.0.jar", "mod-1.0-sources.jar", "mod-1.0-javadoc.jar"],
+        );
+        let artifacts = ArtifactConfig {
+            extra: Some(vec!["build/libs/*-javadoc.jar".to_owned()]),
+            ..Default::default()
+        };
+
+        let jars = get_mod_jars(&dir, &artifacts).unwrap();
+
+        assert_eq!(jars.mod_jar.file_name, "mod-1.0.jar");
+        assert_eq!(jars.sources_jar.unwrap().file_name, "mod-1.0-sources.jar");
+        assert_eq!(jars.extra_files.len(), 1);
+        assert_eq!(jars.extra_files[0].file_name, "mod-1.0-javadoc.jar");
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn excludes_matching_jars() {
+        let dir = test_project("exclude", &["mod-1.0.jar", "mod-1.0-dev.jar"]);
+        let artifacts = ArtifactConfig {
+            exclude: Some(vec!["*-dev.jar".to_owned()]),
+            ..Default::default()
+        };
+
+        let jars = get_mod_jars(&dir, &artifacts).unwrap();
+
+        assert_eq!(jars.mod_jar.file_name, "mod-1.0.jar");
+        assert!(jars.sources_jar.is_none());
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn rejects_multiple_mod_jars() {
+        let dir = test_project("multiple", &["mod-1.0.jar", "mod-1.0-dev.jar"]);
+
+        let err = get_mod_jars(&dir, &ArtifactConfig::default()).unwrap_err();
+
+        assert!(matches!(
+            err.downcast_ref::<PeonyError>(),
+            Some(PeonyError::Artifact(_))
+        ));
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn rejects_invalid_exclude_glob() {
+        let dir = test_project("invalid", &["mod-1.0.jar"]);
+        let artifacts = ArtifactConfig {
+            exclude: Some(vec!["[".to_owned()]),
+            ..Default::default()
+        };
+
+        let err = get_mod_jars(&dir, &artifacts).unwrap_err();
+
+        assert!(matches!(
+            err.downcast_ref::<PeonyError>(),
+            Some(PeonyError::Config(_))
+        ));
+
+        let _ = fs::remove_dir_all(dir);
+    }
+}
@@ -5,7 +5,7 @@ pub struct CreateReleaseRequest {
     pub tag_name: String,
     pub name: Option<String>,
     pub body: Option<String>,
-    pub prerelease: bool
+    pub prerelease: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -84,7 +84,7 @@ pub struct GithubReactions {
     pub total_count: i32,
     #[serde(rename = "+1")]
     pub plus_one: i32,
-    #[serde(rename = "+1")]
+    #[serde(rename = "-1")]
     pub minus_one: i32,
     pub laugh: i32,
     pub confused: i32,
@@ -0,0 +1,49 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+use crate::models::release::{PublishResult, PublisherConfig, Release};
+use crate::models::target::{Target, Targets};
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ReleaseJournal {
+    #[serde(skip)]
+    pub path: PathBuf,
+    pub created_at: String,
+    pub publisher_config: PublisherConfig,
+    pub targets: Targets,
+    pub release: Release,
+    pub results: Vec<PublishResult>,
+}
+
+impl ReleaseJournal {
+    pub fn new(release: Release, publisher_config: PublisherConfig, targets: Targets) -> Self {
+        Self {
+            path: PathBuf::new(),
+            created_at: chrono::Utc::now().to_rfc3339(),
+            publisher_config,
+            targets,
+            release,
+            results: vec![],
+        }
+    }
+
+    pub fn result(&self, target: Target) -> Option<&PublishResult> {
+        self.results.iter().find(|result| result.target == target)
+    }
+
+    pub fn set_result(&mut self, result: PublishResult) {
+        match self.results.iter_mut().find(|r| r.target == result.target) {
+            Some(existing) => *existing = result,
+            None => self.results.push(result),
+        }
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.targets
+            .iter()
+            .all(|target| match self.result(*target) {
+                Some(result) => result.completed,
+                None => false,
+            })
+    }
+}
@@ -1,12 +1,17 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 pub mod github;
+pub mod journal;
 pub mod modrinth;
+pub mod profile;
 pub mod project_type;
+pub mod release;
+pub mod target;
 pub mod util;
 pub mod version;
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
 pub struct GithubConfig {
     pub repo_owner: String,
     pub repo_name: String,
@@ -15,13 +20,13 @@ pub struct GithubConfig {
 // Eventually should be moved to models::project_type::modpack::config
 // unnecessary for now, but should be moved before project types other than modpacks
 // and mods are implemented.
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
 pub struct ModrinthConfig {
     pub project_id: String,
     pub staging: Option<bool>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
 pub struct DiscordConfig {
     pub github_emoji_id: String,
     pub modrinth_emoji_id: String,
@@ -1,8 +1,11 @@
+use clap::ValueEnum;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 pub mod project;
 pub mod version;
 
+#[derive(Debug, Clone)]
 pub struct ModrinthUrl {
     pub labrinth: String,
     pub knossos: String,
@@ -33,7 +36,7 @@ impl ModrinthUrl {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, Copy, Clone)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Copy, Clone, PartialEq, Eq, ValueEnum)]
 #[serde(rename_all = "lowercase")]
 pub enum Loader {
     Quilt,
@@ -80,7 +83,7 @@ pub struct GalleryObject {
     pub ordering: i32,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
 #[serde(rename_all = "lowercase")]
 pub enum DependencyType {
     Required,
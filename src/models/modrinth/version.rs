@@ -1,11 +1,11 @@
-use clap::ValueEnum;
 use crate::models::{
     modrinth::{DependencyType, Loader},
     project_type::mc_mod::config::modrinth::ModrinthDependency,
 };
+use clap::ValueEnum;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-
 // Based on the `Create Version` schema here:
 // https://docs.modrinth.com/api-spec#tag/versions/operation/createVersion
 #[derive(Debug, Serialize, Deserialize)]
@@ -24,7 +24,7 @@ pub struct VersionRequest {
     pub primary_file: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct VersionDependency {
     pub version_id: Option<String>,
     pub project_id: Option<String>,
@@ -32,7 +32,7 @@ pub struct VersionDependency {
     pub dependency_type: DependencyType,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, ValueEnum)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, ValueEnum)]
 #[serde(rename_all = "lowercase")]
 pub enum VersionType {
     Release,
@@ -49,6 +49,28 @@ pub enum VersionStatus {
     Unlisted,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VersionResponse {
+    pub id: String,
+    pub project_id: String,
+    pub version_number: String,
+    pub files: Vec<VersionFile>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VersionFile {
+    pub hashes: VersionFileHashes,
+    pub url: String,
+    pub filename: String,
+    pub primary: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VersionFileHashes {
+    pub sha512: String,
+    pub sha1: String,
+}
+
 impl From<ModrinthDependency> for VersionDependency {
     fn from(dep: ModrinthDependency) -> Self {
         Self {
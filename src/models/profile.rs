@@ -0,0 +1,93 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::error::PeonyError;
+use crate::models::{DiscordConfig, GithubConfig};
+
+// A `[profile.<name>]` table, whose values replace the matching values of
+// the config when it's selected with `--profile`. Unknown keys are errors,
+// since a misspelled override would silently release to the default target.
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct ProfileConfig {
+    pub github: Option<ProfileGithubConfig>,
+    pub modrinth: Option<ProfileModrinthConfig>,
+    pub discord: Option<DiscordConfig>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct ProfileGithubConfig {
+    pub repo_owner: Option<String>,
+    pub repo_name: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct ProfileModrinthConfig {
+    pub project_id: Option<String>,
+    pub staging: Option<bool>,
+}
+
+// The parts of a config a profile can override
+pub struct ProfileTarget<'a> {
+    pub github: &'a mut GithubConfig,
+    pub modrinth_project_id: &'a mut String,
+    pub modrinth_staging: &'a mut Option<bool>,
+    pub discord: &'a mut Option<DiscordConfig>,
+}
+
+pub fn find_profile<'a>(
+    profiles: &'a Option<BTreeMap<String, ProfileConfig>>,
+    name: &str,
+) -> Result<&'a ProfileConfig, anyhow::Error> {
+    let profiles = match profiles {
+        Some(profiles) => profiles,
+        None => {
+            return Err(PeonyError::Config(format!(
+                "Failed to find profile `{}`, config has no profiles",
+                name
+            ))
+            .into())
+        }
+    };
+
+    match profiles.get(name) {
+        Some(profile) => Ok(profile),
+        None => Err(PeonyError::Config(format!(
+            "Failed to find profile `{}`, expected one of: {}",
+            name,
+            profiles.keys().cloned().collect::<Vec<String>>().join(", ")
+        ))
+        .into()),
+    }
+}
+
+impl ProfileConfig {
+    pub fn apply(&self, target: ProfileTarget) {
+        if let Some(github) = &self.github {
+            if let Some(repo_owner) = &github.repo_owner {
+                target.github.repo_owner = repo_owner.clone()
+            }
+
+            if let Some(repo_name) = &github.repo_name {
+                target.github.repo_name = repo_name.clone()
+            }
+        }
+
+        if let Some(modrinth) = &self.modrinth {
+            if let Some(project_id) = &modrinth.project_id {
+                *target.modrinth_project_id = project_id.clone()
+            }
+
+            if modrinth.staging.is_some() {
+                *target.modrinth_staging = modrinth.staging
+            }
+        }
+
+        if self.discord.is_some() {
+            *target.discord = self.discord.clone()
+        }
+    }
+}
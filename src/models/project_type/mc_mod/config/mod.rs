@@ -1,12 +1,17 @@
+use crate::models::profile::{find_profile, ProfileConfig, ProfileTarget};
 use crate::models::{
-    modrinth::Loader, project_type::mc_mod::config::modrinth::ModrinthConfig, DiscordConfig,
-    GithubConfig,
+    modrinth::Loader,
+    project_type::mc_mod::config::modrinth::{ModrinthConfig, ModrinthDependency},
+    DiscordConfig, GithubConfig,
 };
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
 
 pub mod modrinth;
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
 pub struct ModConfig {
     pub config_format_version: i32,
     pub version_name_format: String,
@@ -17,4 +22,95 @@ pub struct ModConfig {
     pub github: GithubConfig,
     pub modrinth: ModrinthConfig,
     pub discord: Option<DiscordConfig>,
+    // Undo already published releases if publishing to any target fails
+    pub rollback: Option<bool>,
+    #[serde(rename = "profile")]
+    pub profiles: Option<BTreeMap<String, ProfileConfig>>,
+    // Per-loader Gradle subprojects, like in Architectury projects. Detected
+    // from directories named after a loader if not set.
+    #[serde(rename = "subproject")]
+    pub subprojects: Option<Vec<SubprojectConfig>>,
+    pub artifacts: Option<ArtifactConfig>,
+    pub gradle: Option<GradleConfig>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
+pub struct GradleConfig {
+    // Defaults to `["build"]`, replaced by `--gradle-tasks`
+    pub tasks: Option<Vec<String>>,
+    // Passed to Gradle as `-Pkey=value`
+    pub properties: Option<BTreeMap<String, String>>,
+    // In seconds
+    pub timeout: Option<u64>,
+}
+
+// Globs are relative to the project directory, or to each subproject's
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Default)]
+pub struct ArtifactConfig {
+    // Has to match exactly one jar, defaults to `build/libs/*.jar`
+    pub primary: Option<String>,
+    // Defaults to `build/libs/*-sources.jar`
+    pub sources: Option<String>,
+    // Uploaded alongside the mod jar, e.g. `build/libs/*-javadoc.jar`
+    pub extra: Option<Vec<String>>,
+    // Matched against file names, e.g. `*-dev.jar` or `*-all.jar`
+    pub exclude: Option<Vec<String>>,
+}
+
+impl ArtifactConfig {
+    pub const DEFAULT_PRIMARY: &'static str = "build/libs/*.jar";
+    pub const DEFAULT_SOURCES: &'static str = "build/libs/*-sources.jar";
+
+    pub fn primary(&self) -> &str {
+        self.primary.as_deref().unwrap_or(Self::DEFAULT_PRIMARY)
+    }
+
+    pub fn sources(&self) -> &str {
+        self.sources.as_deref().unwrap_or(Self::DEFAULT_SOURCES)
+    }
+
+    // Every glob in the config, along with its key
+    pub fn globs(&self) -> Vec<(&'static str, &str)> {
+        let mut globs = vec![("primary", self.primary()), ("sources", self.sources())];
+
+        for glob in self.extra.iter().flatten() {
+            globs.push(("extra", glob))
+        }
+
+        for glob in self.exclude.iter().flatten() {
+            globs.push(("exclude", glob))
+        }
+
+        globs
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
+pub struct SubprojectConfig {
+    // Relative to the project directory
+    pub path: PathBuf,
+    // Defaults to the loader of the subproject's jar
+    pub loaders: Option<Vec<Loader>>,
+    // Replaces the config's `mc_versions` on this subproject's Modrinth
+    // version
+    pub mc_versions: Option<Vec<String>>,
+    // Added to the config's `modrinth.dependency` list, which is shared by
+    // every subproject, e.g. Fabric API for the Fabric subproject only
+    #[serde(rename = "dependency")]
+    pub dependencies: Option<Vec<ModrinthDependency>>,
+}
+
+impl ModConfig {
+    pub fn apply_profile(&mut self, name: &str) -> Result<(), anyhow::Error> {
+        let profile = find_profile(&self.profiles, name)?.clone();
+
+        profile.apply(ProfileTarget {
+            github: &mut self.github,
+            modrinth_project_id: &mut self.modrinth.project_id,
+            modrinth_staging: &mut self.modrinth.staging,
+            discord: &mut self.discord,
+        });
+
+        Ok(())
+    }
 }
@@ -1,8 +1,9 @@
 use crate::models::modrinth::version::VersionDependency;
 use crate::models::modrinth::DependencyType;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
 pub struct ModrinthConfig {
     pub project_id: String,
     pub staging: Option<bool>,
@@ -10,7 +11,7 @@ pub struct ModrinthConfig {
     pub dependencies: Option<Vec<ModrinthDependency>>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
 pub struct ModrinthDependency {
     pub version_id: Option<String>,
     pub project_id: Option<String>,
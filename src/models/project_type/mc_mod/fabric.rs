@@ -0,0 +1,37 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::models::util::OneOrMany;
+
+// https://fabricmc.net/wiki/documentation:fabric_mod_json_spec
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FabricModJson {
+    pub id: String,
+    pub version: String,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub authors: Vec<FabricPerson>,
+    #[serde(default)]
+    pub contact: BTreeMap<String, String>,
+    pub license: Option<OneOrMany<String>>,
+    // Mod ids mapped to one or more version ranges
+    #[serde(default)]
+    pub depends: BTreeMap<String, OneOrMany<String>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(untagged)]
+pub enum FabricPerson {
+    Name(String),
+    Details { name: String },
+}
+
+impl FabricPerson {
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Name(name) => name,
+            Self::Details { name } => name,
+        }
+    }
+}
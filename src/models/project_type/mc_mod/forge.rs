@@ -0,0 +1,50 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+pub const FORGE_MODS_TOML: &str = "META-INF/mods.toml";
+// NeoForge 20.5 and later, older versions use `mods.toml` like Forge
+pub const NEOFORGE_MODS_TOML: &str = "META-INF/neoforge.mods.toml";
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ModsToml {
+    pub license: Option<String>,
+    #[serde(rename = "issueTrackerURL")]
+    pub issue_tracker_url: Option<String>,
+    pub mods: Vec<ModsTomlMod>,
+    // Keyed by the mod id the dependencies belong to
+    #[serde(default)]
+    pub dependencies: HashMap<String, Vec<ModsTomlDependency>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ModsTomlMod {
+    pub mod_id: String,
+    pub display_name: Option<String>,
+    // Usually `${file.jarVersion}`, which is the jar manifest's
+    // `Implementation-Version`
+    pub version: Option<String>,
+    pub description: Option<String>,
+    pub authors: Option<String>,
+    #[serde(rename = "displayURL")]
+    pub display_url: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ModsTomlDependency {
+    pub mod_id: String,
+    pub version_range: Option<String>,
+}
+
+impl ModsToml {
+    // NeoForge mods that still use `mods.toml` can only be told apart by
+    // their dependency on NeoForge
+    pub fn depends_on_neoforge(&self) -> bool {
+        self.dependencies
+            .values()
+            .flatten()
+            .any(|dep| dep.mod_id == "neoforge")
+    }
+}
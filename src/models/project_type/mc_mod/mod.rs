@@ -1,7 +1,15 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 
+use crate::models::modrinth::Loader;
+use crate::models::project_type::mc_mod::config::SubprojectConfig;
+use crate::models::project_type::mc_mod::version::ModVersionInfo;
+
 pub mod config;
+pub mod fabric;
+pub mod forge;
+pub mod quilt;
 pub mod version;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -9,12 +17,29 @@ pub struct ModInfo {
     pub id: String,
     pub name: String,
     pub version: String,
+    pub loader: Loader,
+    pub description: Option<String>,
+    pub authors: Vec<String>,
+    pub licenses: Vec<String>,
+    // e.g. `homepage`, `sources` and `issues`
+    pub contact: BTreeMap<String, String>,
+    pub dependencies: Vec<ModDependency>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ModDependency {
+    pub id: String,
+    // In the loader's own syntax, e.g. `>=1.20` for Fabric and Quilt, or
+    // `[1.20,1.21)` for Forge and NeoForge
+    pub version_ranges: Vec<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ModJars {
     pub mod_jar: Jar,
     pub sources_jar: Option<Jar>,
+    #[serde(default)]
+    pub extra_files: Vec<Jar>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -22,3 +47,13 @@ pub struct Jar {
     pub file_name: String,
     pub file_path: PathBuf,
 }
+
+// The jars built for one set of loaders. Multi-loader projects have one of
+// these per loader subproject.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ModBuild {
+    pub jars: ModJars,
+    pub mod_info: ModInfo,
+    pub version_info: ModVersionInfo,
+    pub subproject: Option<SubprojectConfig>,
+}
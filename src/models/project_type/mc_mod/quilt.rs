@@ -0,0 +1,103 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::models::util::OneOrMany;
+
+// https://github.com/QuiltMC/rfcs/blob/main/specification/0002-quilt.mod.json.md
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct QuiltModJson {
+    pub quilt_loader: QuiltLoader,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct QuiltLoader {
+    pub id: String,
+    pub version: String,
+    #[serde(default)]
+    pub metadata: QuiltMetadata,
+    #[serde(default)]
+    pub depends: Vec<QuiltDependency>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct QuiltMetadata {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    // Contributor names mapped to their roles
+    #[serde(default)]
+    pub contributors: BTreeMap<String, OneOrMany<String>>,
+    #[serde(default)]
+    pub contact: BTreeMap<String, String>,
+    pub license: Option<OneOrMany<QuiltLicense>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(untagged)]
+pub enum QuiltLicense {
+    Id(String),
+    Details { id: String },
+}
+
+impl QuiltLicense {
+    pub fn id(&self) -> &str {
+        match self {
+            Self::Id(id) => id,
+            Self::Details { id } => id,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(untagged)]
+pub enum QuiltDependency {
+    Id(String),
+    // `versions` is either a range, a list of ranges or an `any`/`all`
+    // object of ranges
+    Details {
+        id: String,
+        versions: Option<serde_json::Value>,
+    },
+    // Satisfied by any one of the dependencies
+    AnyOf(Vec<QuiltDependency>),
+}
+
+impl QuiltDependency {
+    // `None` for `AnyOf`, see `flatten`
+    pub fn id(&self) -> Option<&str> {
+        match self {
+            Self::Id(id) => Some(id),
+            Self::Details { id, .. } => Some(id),
+            Self::AnyOf(_) => None,
+        }
+    }
+
+    // The dependency itself, or every alternative of an `AnyOf`
+    pub fn flatten(&self) -> Vec<&QuiltDependency> {
+        match self {
+            Self::AnyOf(dependencies) => dependencies
+                .iter()
+                .flat_map(|dependency| dependency.flatten())
+                .collect(),
+            _ => vec![self],
+        }
+    }
+
+    pub fn version_ranges(&self) -> Vec<String> {
+        match self {
+            Self::Details {
+                versions: Some(versions),
+                ..
+            } => collect_strings(versions),
+            _ => vec![],
+        }
+    }
+}
+
+fn collect_strings(value: &serde_json::Value) -> Vec<String> {
+    match value {
+        serde_json::Value::String(string) => vec![string.clone()],
+        serde_json::Value::Array(values) => values.iter().flat_map(collect_strings).collect(),
+        serde_json::Value::Object(map) => map.values().flat_map(collect_strings).collect(),
+        _ => vec![],
+    }
+}
@@ -1,7 +1,6 @@
 use crate::models::modrinth::Loader;
 use crate::models::project_type::mc_mod::config::ModConfig;
-use crate::models::project_type::mc_mod::{ModInfo, ModJars};
-use crate::util::read_file;
+use crate::models::project_type::mc_mod::ModInfo;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -9,39 +8,15 @@ pub struct ModVersionInfo {
     pub name: String,
     pub version: String,
     pub loaders: Vec<Loader>,
-    pub mod_file: ModFile,
-    pub sources_file: Option<ModFile>,
-}
-
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct ModFile {
-    pub name: String,
-    pub contents: Vec<u8>,
 }
 
 impl ModVersionInfo {
     pub fn new(
         config: &ModConfig,
-        mod_jars: &ModJars,
         mod_info: &ModInfo,
+        loaders: &[Loader],
     ) -> Result<Self, anyhow::Error> {
-        let mod_jar_contents = read_file(&mod_jars.mod_jar.file_path)?;
-        let sources_jar_contents = match &mod_jars.sources_jar {
-            Some(jar) => Some(read_file(&jar.file_path)?),
-            None => None,
-        };
-
-        let mod_jar_info = ModFile {
-            name: mod_jars.mod_jar.file_name.clone(),
-            contents: mod_jar_contents,
-        };
-
-        let sources_jar_info = sources_jar_contents.map(|contents| ModFile {
-            name: mod_jars.sources_jar.clone().unwrap().file_name,
-            contents,
-        });
-
-        let loaders = config.loaders.clone();
+        let loaders = loaders.to_vec();
 
         let loaders_formatted: String = loaders
             .iter()
@@ -65,8 +40,6 @@ impl ModVersionInfo {
             name: version_name,
             version: mod_info.version.clone(),
             loaders,
-            mod_file: mod_jar_info,
-            sources_file: sources_jar_info,
         })
     }
 }
@@ -1,11 +1,33 @@
+use crate::models::profile::{find_profile, ProfileConfig, ProfileTarget};
 use crate::models::{DiscordConfig, GithubConfig, ModrinthConfig};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
 pub struct ModpackConfig {
     pub config_format_version: i32,
     pub version_name_format: String,
     pub github: GithubConfig,
     pub modrinth: ModrinthConfig,
     pub discord: Option<DiscordConfig>,
+    // Undo already published releases if publishing to any target fails
+    pub rollback: Option<bool>,
+    #[serde(rename = "profile")]
+    pub profiles: Option<BTreeMap<String, ProfileConfig>>,
+}
+
+impl ModpackConfig {
+    pub fn apply_profile(&mut self, name: &str) -> Result<(), anyhow::Error> {
+        let profile = find_profile(&self.profiles, name)?.clone();
+
+        profile.apply(ProfileTarget {
+            github: &mut self.github,
+            modrinth_project_id: &mut self.modrinth.project_id,
+            modrinth_staging: &mut self.modrinth.staging,
+            discord: &mut self.discord,
+        });
+
+        Ok(())
+    }
 }
@@ -0,0 +1,532 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+use crate::dry_run::DryRun;
+use crate::error::PeonyError;
+use crate::models::modrinth::version::{VersionDependency, VersionType};
+use crate::models::modrinth::Loader;
+use crate::models::project_type::mc_mod::config::ModConfig;
+use crate::models::project_type::mc_mod::version::ModVersionInfo;
+use crate::models::project_type::mc_mod::ModBuild;
+use crate::models::project_type::modpack::{config::ModpackConfig, PackFile};
+use crate::models::target::Target;
+use crate::models::util::OutputFileInfo;
+use crate::models::version::VersionInfo;
+use crate::models::{DiscordConfig, GithubConfig};
+use crate::util::sha512_hash;
+
+// Everything a publisher needs to know about a release, independent of
+// the project type it was built from.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Release {
+    pub version_name: String,
+    pub version_number: String,
+    pub changelog: String,
+    pub version_type: VersionType,
+    pub loaders: Vec<Loader>,
+    pub game_versions: Vec<String>,
+    pub dependencies: Vec<VersionDependency>,
+    pub files: Vec<ReleaseFile>,
+    // Multi-loader releases are published as one Modrinth version per
+    // variant, but as a single GitHub release with every file
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub variants: Vec<ReleaseVariant>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ReleaseVariant {
+    pub version_name: String,
+    pub version_number: String,
+    pub loaders: Vec<Loader>,
+    // Names of this variant's files in `Release::files`
+    pub file_names: Vec<String>,
+    // Replace the release's, if set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub game_versions: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dependencies: Option<Vec<VersionDependency>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ReleaseFile {
+    pub file_name: String,
+    pub file_path: PathBuf,
+    pub file_type: FileType,
+    pub sha512: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Copy, Clone, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum FileType {
+    Mod,
+    Sources,
+    Modpack,
+    Extra,
+}
+
+impl FileType {
+    pub fn part_name(&self) -> String {
+        match self {
+            Self::Mod => "mod_jar",
+            Self::Sources => "sources_jar",
+            Self::Modpack => "file",
+            Self::Extra => "extra_file",
+        }
+        .to_string()
+    }
+
+    pub fn content_type(&self) -> String {
+        match self {
+            Self::Mod | Self::Sources => "application/java-archive",
+            Self::Modpack => "application/zip",
+            Self::Extra => "application/octet-stream",
+        }
+        .to_string()
+    }
+}
+
+// The parts of a project config publishers need, so a release can be
+// published again without the original config file.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PublisherConfig {
+    pub github: GithubConfig,
+    pub modrinth_project_id: String,
+    pub modrinth_staging: Option<bool>,
+    pub discord: Option<DiscordConfig>,
+    pub rollback: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PublishResult {
+    pub target: Target,
+    pub id: Option<String>,
+    pub url: Option<String>,
+    pub files: Vec<PublishedFile>,
+    pub completed: bool,
+    // Every version published for a multi-loader release, `id` and `url`
+    // are those of the first one
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub versions: Vec<PublishedVersion>,
+    // Whether the GitHub tag was created by this release, rolling back only
+    // deletes tags that didn't exist before it
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub created_tag: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PublishedVersion {
+    pub version_name: String,
+    pub id: String,
+    pub url: Option<String>,
+    // Names of this version's files in `PublishResult::files`
+    #[serde(default)]
+    pub file_names: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PublishedFile {
+    pub file_name: String,
+    pub url: Option<String>,
+    pub sha512: String,
+}
+
+// Written once a release finishes, for CI steps that need its links
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ReleaseSummary {
+    pub version_name: String,
+    pub version_number: String,
+    pub version_type: VersionType,
+    pub files: Vec<SummaryFile>,
+    pub results: Vec<PublishResult>,
+    // Only set for dry runs, which publish nothing
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dry_run: Option<DryRun>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SummaryFile {
+    pub file_name: String,
+    pub sha512: String,
+}
+
+impl Release {
+    // `version_info` describes the release as a whole, e.g. the GitHub
+    // release, with the loaders of every build
+    pub fn from_mod(
+        config: &ModConfig,
+        builds: &[ModBuild],
+        version_info: &ModVersionInfo,
+        changelog: &str,
+        version_type: VersionType,
+    ) -> Result<Self, anyhow::Error> {
+        let mut dependencies: Vec<VersionDependency> = config
+            .modrinth
+            .dependencies
+            .iter()
+            .flatten()
+            .map(|dep| VersionDependency::from(dep.clone()))
+            .collect();
+
+        let mut files: Vec<ReleaseFile> = vec![];
+        let mut variants = vec![];
+
+        for build in builds {
+            let mut build_files = vec![ReleaseFile::new(
+                &build.jars.mod_jar.file_name,
+                &build.jars.mod_jar.file_path,
+                FileType::Mod,
+            )?];
+
+            if let Some(jar) = &build.jars.sources_jar {
+                build_files.push(ReleaseFile::new(
+                    &jar.file_name,
+                    &jar.file_path,
+                    FileType::Sources,
+                )?)
+            }
+
+            for file in &build.jars.extra_files {
+                build_files.push(ReleaseFile::new(
+                    &file.file_name,
+                    &file.file_path,
+                    FileType::Extra,
+                )?)
+            }
+
+            for file in &build_files {
+                if files.iter().any(|f| f.file_name == file.file_name) {
+                    return Err(PeonyError::Artifact(format!(
+                        "Found more than one jar named `{}`, jars of different loaders need \
+                         different names",
+                        file.file_name
+                    ))
+                    .into());
+                }
+            }
+
+            variants.push(ReleaseVariant {
+                version_name: build.version_info.name.clone(),
+                version_number: build.version_info.version.clone(),
+                loaders: build.version_info.loaders.clone(),
+                file_names: build_files
+                    .iter()
+                    .map(|file| file.file_name.clone())
+                    .collect(),
+                game_versions: build
+                    .subproject
+                    .as_ref()
+                    .and_then(|subproject| subproject.mc_versions.clone()),
+                // The config's dependencies are shared by every subproject
+                dependencies: build
+                    .subproject
+                    .as_ref()
+                    .and_then(|subproject| subproject.dependencies.as_ref())
+                    .map(|subproject_deps| {
+                        dependencies
+                            .iter()
+                            .cloned()
+                            .chain(
+                                subproject_deps
+                                    .iter()
+                                    .map(|dep| VersionDependency::from(dep.clone())),
+                            )
+                            .collect()
+                    }),
+            });
+
+            files.append(&mut build_files);
+        }
+
+        let mut game_versions = config.mc_versions.clone();
+
+        // A single build is published as is
+        if variants.len() == 1 {
+            let variant = variants.remove(0);
+
+            if let Some(variant_game_versions) = variant.game_versions {
+                game_versions = variant_game_versions
+            }
+
+            if let Some(variant_dependencies) = variant.dependencies {
+                dependencies = variant_dependencies
+            }
+        }
+
+        Ok(Self {
+            version_name: version_info.name.clone(),
+            version_number: version_info.version.clone(),
+            changelog: changelog.to_owned(),
+            version_type,
+            loaders: version_info.loaders.clone(),
+            game_versions,
+            dependencies,
+            files,
+            variants,
+        })
+    }
+
+    pub fn from_modpack(
+        pack_file: &PackFile,
+        output_file_info: &OutputFileInfo,
+        version_info: &VersionInfo,
+        changelog: &str,
+        version_type: VersionType,
+    ) -> Result<Self, anyhow::Error> {
+        Ok(Self {
+            version_name: version_info.version_name.clone(),
+            version_number: pack_file.version.clone(),
+            changelog: changelog.to_owned(),
+            version_type,
+            loaders: vec![version_info.loader],
+            game_versions: vec![pack_file.versions.minecraft.clone()],
+            dependencies: vec![],
+            files: vec![ReleaseFile::new(
+                &output_file_info.file_name,
+                &output_file_info.file_path,
+                FileType::Modpack,
+            )?],
+            variants: vec![],
+        })
+    }
+
+    // The release of a single variant, as it's published on targets that
+    // get one version per variant
+    pub fn variant_release(&self, variant: &ReleaseVariant) -> Self {
+        Self {
+            version_name: variant.version_name.clone(),
+            version_number: variant.version_number.clone(),
+            loaders: variant.loaders.clone(),
+            game_versions: variant
+                .game_versions
+                .clone()
+                .unwrap_or(self.game_versions.clone()),
+            dependencies: variant
+                .dependencies
+                .clone()
+                .unwrap_or(self.dependencies.clone()),
+            files: self
+                .files
+                .iter()
+                .filter(|file| variant.file_names.contains(&file.file_name))
+                .cloned()
+                .collect(),
+            variants: vec![],
+            ..self.clone()
+        }
+    }
+
+    // Every variant's release, or just this release if it has no variants
+    pub fn variant_releases(&self) -> Vec<Self> {
+        if self.variants.is_empty() {
+            vec![self.clone()]
+        } else {
+            self.variants
+                .iter()
+                .map(|variant| self.variant_release(variant))
+                .collect()
+        }
+    }
+
+    pub fn primary_file(&self) -> Option<&ReleaseFile> {
+        self.files
+            .iter()
+            .find(|file| matches!(file.file_type, FileType::Mod | FileType::Modpack))
+    }
+}
+
+impl ReleaseFile {
+    // Multipart part names have to be unique, and there can be any number of
+    // extra files
+    pub fn part_name(&self) -> String {
+        match self.file_type {
+            FileType::Extra => self.file_name.clone(),
+            file_type => file_type.part_name(),
+        }
+    }
+
+    pub fn new(
+        file_name: &str,
+        file_path: &PathBuf,
+        file_type: FileType,
+    ) -> Result<Self, anyhow::Error> {
+        Ok(Self {
+            file_name: file_name.to_owned(),
+            file_path: file_path.clone(),
+            file_type,
+            sha512: sha512_hash(file_path)?,
+        })
+    }
+}
+
+impl From<&ModConfig> for PublisherConfig {
+    fn from(config: &ModConfig) -> Self {
+        Self {
+            github: config.github.clone(),
+            modrinth_project_id: config.modrinth.project_id.clone(),
+            modrinth_staging: config.modrinth.staging,
+            discord: config.discord.clone(),
+            rollback: config.rollback,
+        }
+    }
+}
+
+impl From<&ModpackConfig> for PublisherConfig {
+    fn from(config: &ModpackConfig) -> Self {
+        Self {
+            github: config.github.clone(),
+            modrinth_project_id: config.modrinth.project_id.clone(),
+            modrinth_staging: config.modrinth.staging,
+            discord: config.discord.clone(),
+            rollback: config.rollback,
+        }
+    }
+}
+
+impl ReleaseSummary {
+    pub fn new(release: &Release, results: Vec<PublishResult>) -> Self {
+        Self {
+            version_name: release.version_name.clone(),
+            version_number: release.version_number.clone(),
+            version_type: release.version_type.clone(),
+            files: release
+                .files
+                .iter()
+                .map(|file| SummaryFile {
+                    file_name: file.file_name.clone(),
+                    sha512: file.sha512.clone(),
+                })
+                .collect(),
+            results,
+            dry_run: None,
+        }
+    }
+
+    pub fn with_dry_run(mut self, dry_run: DryRun) -> Self {
+        self.dry_run = Some(dry_run);
+        self
+    }
+}
+
+impl PublishResult {
+    pub fn new(target: Target) -> Self {
+        Self {
+            target,
+            id: None,
+            url: None,
+            files: vec![],
+            completed: false,
+            versions: vec![],
+            created_tag: false,
+        }
+    }
+
+    pub fn has_file(&self, file_name: &str) -> bool {
+        self.files.iter().any(|file| file.file_name == file_name)
+    }
+
+    // Forgets a version deleted by a rollback, along with its files, so
+    // resuming publishes it again. `id` and `url` move on to the first
+    // version that's left.
+    pub fn remove_version(&mut self, version_id: &str) {
+        match self
+            .versions
+            .iter()
+            .position(|version| version.id == version_id)
+        {
+            Some(index) => {
+                let version = self.versions.remove(index);
+                self.files
+                    .retain(|file| !version.file_names.contains(&file.file_name));
+            }
+            // A release without variants is a single version
+            None if self.versions.is_empty() => self.files.clear(),
+            None => return,
+        }
+
+        match self.versions.first() {
+            Some(version) => {
+                self.id = Some(version.id.clone());
+                self.url = version.url.clone();
+            }
+            None => {
+                self.id = None;
+                self.url = None;
+            }
+        }
+
+        self.completed = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn published_file(file_name: &str) -> PublishedFile {
+        PublishedFile {
+            file_name: file_name.to_owned(),
+            url: None,
+            sha512: String::new(),
+        }
+    }
+
+    fn published_version(id: &str, file_names: &[&str]) -> PublishedVersion {
+        PublishedVersion {
+            version_name: id.to_owned(),
+            id: id.to_owned(),
+            url: Some(format!("https://modrinth.com/version/{}", id)),
+            file_names: file_names.iter().map(|name| name.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn partial_rollback_keeps_remaining_version() {
+        let mut result = PublishResult::new(Target::Modrinth);
+        result.id = Some("fabric".to_owned());
+        result.url = Some("https://modrinth.com/version/fabric".to_owned());
+        result.completed = true;
+        result.versions = vec![
+            published_version("fabric", &["mod-fabric.jar", "mod-fabric-sources.jar"]),
+            published_version("neoforge", &["mod-neoforge.jar"]),
+        ];
+        result.files = vec![
+            published_file("mod-fabric.jar"),
+            published_file("mod-fabric-sources.jar"),
+            published_file("mod-neoforge.jar"),
+        ];
+
+        // Deleting the NeoForge version failed after the Fabric one was deleted
+        result.remove_version("fabric");
+
+        assert!(!result.completed);
+        assert!(!result.has_file("mod-fabric.jar"));
+        assert!(!result.has_file("mod-fabric-sources.jar"));
+        assert!(result.has_file("mod-neoforge.jar"));
+        assert_eq!(result.versions.len(), 1);
+        assert_eq!(result.id.as_deref(), Some("neoforge"));
+        assert_eq!(
+            result.url.as_deref(),
+            Some("https://modrinth.com/version/neoforge")
+        );
+
+        result.remove_version("neoforge");
+
+        assert!(result.files.is_empty());
+        assert!(result.id.is_none());
+        assert!(result.url.is_none());
+    }
+
+    #[test]
+    fn rollback_of_single_version() {
+        let mut result = PublishResult::new(Target::Modrinth);
+        result.id = Some("abc".to_owned());
+        result.completed = true;
+        result.files = vec![published_file("mod.jar")];
+
+        result.remove_version("abc");
+
+        assert!(!result.completed);
+        assert!(result.files.is_empty());
+        assert!(result.id.is_none());
+    }
+}
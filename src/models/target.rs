@@ -0,0 +1,75 @@
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Copy, Clone, PartialEq, Eq, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Target {
+    Github,
+    Modrinth,
+    Discord,
+}
+
+impl Target {
+    pub fn formatted(&self) -> String {
+        match self {
+            Self::Github => "GitHub",
+            Self::Modrinth => "Modrinth",
+            Self::Discord => "Discord",
+        }
+        .to_string()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Targets(Vec<Target>);
+
+impl Targets {
+    // Discord isn't a default target, so it has to be requested with either
+    // `--discord` or `--only discord`. `--discord` adds it to `--only`'s
+    // targets too.
+    pub fn new(only: &[Target], skip: &[Target], discord: bool) -> Self {
+        let mut targets = if only.is_empty() {
+            vec![Target::Github, Target::Modrinth]
+        } else {
+            only.to_vec()
+        };
+
+        if discord && !targets.contains(&Target::Discord) {
+            targets.push(Target::Discord)
+        }
+
+        targets.retain(|target| !skip.contains(target));
+
+        Self(targets)
+    }
+
+    pub fn contains(&self, target: Target) -> bool {
+        self.0.contains(&target)
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Target> {
+        self.0.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn discord_is_added_to_only() {
+        let targets = Targets::new(&[Target::Modrinth], &[], true);
+
+        assert!(targets.contains(Target::Modrinth));
+        assert!(targets.contains(Target::Discord));
+        assert!(!targets.contains(Target::Github));
+    }
+
+    #[test]
+    fn discord_is_opt_in() {
+        let targets = Targets::new(&[], &[], false);
+
+        assert!(!targets.contains(Target::Discord));
+        assert_eq!(targets.iter().count(), 2);
+    }
+}
@@ -1,7 +1,7 @@
+use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
 pub struct TempInfo {
-    pub dir_name: String,
     pub dir_path: PathBuf,
 }
 
@@ -9,3 +9,20 @@ pub struct OutputFileInfo {
     pub file_name: String,
     pub file_path: PathBuf,
 }
+
+// Loader metadata often allows either a single value or a list of them
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(untagged)]
+pub enum OneOrMany<T> {
+    One(T),
+    Many(Vec<T>),
+}
+
+impl<T> OneOrMany<T> {
+    pub fn into_vec(self) -> Vec<T> {
+        match self {
+            Self::One(value) => vec![value],
+            Self::Many(values) => values,
+        }
+    }
+}
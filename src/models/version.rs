@@ -1,7 +1,10 @@
 use crate::models::modrinth::Loader;
 
+// Placeholders that can be used in `version_name_format`
+pub const VERSION_NAME_PLACEHOLDERS: [&str; 4] =
+    ["project_name", "project_version", "mc_version", "loader"];
+
 pub struct VersionInfo {
     pub loader: Loader,
     pub version_name: String,
-    pub file_contents: Vec<u8>,
 }
@@ -1,231 +1,363 @@
-use crate::models::modrinth::version::VersionDependency;
-use crate::models::project_type::mc_mod::config::ModConfig;
-use crate::models::project_type::mc_mod::version::ModVersionInfo;
-use crate::models::{
-    modrinth::{
-        version::{VersionRequest, VersionStatus, VersionType},
-        ModrinthUrl,
-    },
-    project_type::modpack::{config::ModpackConfig, PackFile},
-    util::OutputFileInfo,
-    version::VersionInfo,
-};
 use anyhow::anyhow;
+use async_trait::async_trait;
+use indicatif::ProgressBar;
 use reqwest::multipart::{Form, Part};
+use reqwest::StatusCode;
 use std::env;
+use tracing::info;
 
-#[derive(Debug)]
-pub struct VersionForm {
-    pub form: Form,
-    pub part_names: Vec<String>,
+use crate::dry_run::{dry_run_request, DryRunRequest};
+use crate::error::PeonyError;
+use crate::models::modrinth::{
+    version::{VersionRequest, VersionResponse, VersionStatus},
+    ModrinthUrl,
+};
+use crate::models::release::{PublishResult, PublishedFile, PublishedVersion, Release};
+use crate::models::target::Target;
+use crate::progress::{file_body, file_size, upload_progress_bar};
+use crate::publisher::Publisher;
+use crate::util::send_request;
+
+pub struct ModrinthPublisher {
+    pub project_id: String,
+    pub modrinth_url: ModrinthUrl,
 }
 
-#[derive(Debug)]
-pub struct JarPart {
-    pub file_name: String,
-    pub file_part: Part,
-    pub file_type: FileType,
+pub fn get_modrinth_token() -> Result<String, anyhow::Error> {
+    match env::var("MODRINTH_TOKEN") {
+        Ok(token) => Ok(token),
+        Err(err) => Err(PeonyError::Auth(format!(
+            "Failed to get Modrinth token from environment: {}",
+            err
+        ))
+        .into()),
+    }
 }
 
-#[derive(Debug, Copy, Clone)]
-pub enum FileType {
-    Mod,
-    Sources,
+pub fn version_request(release: &Release, project_id: &str) -> VersionRequest {
+    VersionRequest {
+        name: release.version_name.clone(),
+        version_number: release.version_number.clone(),
+        changelog: Some(release.changelog.clone()),
+        dependencies: release.dependencies.clone(),
+        game_versions: release.game_versions.clone(),
+        version_type: release.version_type.clone(),
+        loaders: release.loaders.clone(),
+        featured: false,
+        requested_status: VersionStatus::Listed,
+        project_id: project_id.to_owned(),
+        file_parts: release.files.iter().map(|file| file.part_name()).collect(),
+        primary_file: match release.primary_file() {
+            Some(file) => file.part_name(),
+            None => String::new(),
+        },
+    }
 }
 
-impl FileType {
-    pub fn part_name(&self) -> String {
-        match self {
-            Self::Mod => "mod_jar",
-            Self::Sources => "sources_jar",
-        }
-        .to_string()
+pub async fn create_version_form(
+    release: &Release,
+    request_data: &VersionRequest,
+    progress_bar: &ProgressBar,
+) -> Result<Form, anyhow::Error> {
+    let form_data = serde_json::to_string(request_data)
+        .map_err(|err| anyhow!("Failed to serialize version request body: {}", err))?;
+
+    let mut form = Form::new().text("data", form_data);
+
+    for file in &release.files {
+        let file_part = match Part::stream_with_length(
+            file_body(&file.file_path, progress_bar).await?,
+            file_size(&file.file_path)?,
+        )
+        .file_name(file.file_name.clone())
+        .mime_str(&file.file_type.content_type())
+        {
+            Ok(part) => part,
+            Err(err) => {
+                return Err(anyhow!(
+                    "Failed to get part from `{}`: {}",
+                    file.file_name,
+                    err
+                ))
+            }
+        };
+
+        form = form.part(file.part_name(), file_part);
     }
+
+    Ok(form)
 }
 
-pub async fn create_modpack_release(
-    config: &ModpackConfig,
-    pack_file: &PackFile,
-    output_file_info: &OutputFileInfo,
-    version_info: &VersionInfo,
-    changelog: &String,
-    modrinth_token: String,
-    modrinth_url: &ModrinthUrl,
-    version_type: VersionType
-) -> Result<(), anyhow::Error> {
-    let modrinth_config = config.modrinth.clone();
-
-    println!("Uploading to Modrinth...");
-
-    let modrinth_req = VersionRequest {
-        name: version_info.version_name.clone(),
-        version_number: pack_file.version.clone(),
-        changelog: Some(changelog.to_string()),
-        dependencies: vec![],
-        game_versions: vec![pack_file.versions.minecraft.clone()],
-        version_type,
-        loaders: vec![version_info.loader],
-        featured: false,
-        requested_status: VersionStatus::Listed,
-        project_id: modrinth_config.project_id,
-        file_parts: vec!["file".to_string()],
-        primary_file: output_file_info.file_name.clone(),
-    };
-
-    let file_part = match Part::bytes(version_info.file_contents.clone())
-        .file_name(output_file_info.file_name.clone())
-        .mime_str("application/zip")
+async fn get_modrinth_status(url: &str, token: &str) -> Result<StatusCode, anyhow::Error> {
+    match send_request(
+        reqwest::Client::new()
+            .get(url)
+            .header("Authorization", token),
+    )
+    .await
     {
-        Ok(part) => part,
-        Err(err) => return Err(anyhow!("Failed to get part from .mrpack file: {}", err)),
-    };
-
-    let form = Form::new()
-        .text("data", serde_json::to_string(&modrinth_req).unwrap())
-        .part("file", file_part);
-
-    let req = match reqwest::Client::new()
-        .post(format!("{}/version", modrinth_url.labrinth))
-        .header("Authorization", &modrinth_token)
-        .multipart(form)
-        .send()
-        .await
-    {
-        Ok(res) => res,
-        Err(err) => return Err(anyhow!("Error uploading version: {}", err)),
-    };
-
-    if req.status().is_success() {
-        Ok(println!("Successfully uploaded version to Modrinth!"))
-    } else {
-        Err(anyhow!(
-            "Failed to upload version to Modrinth: {}",
-            req.text().await.unwrap()
-        ))
+        Ok(res) => Ok(res.status()),
+        Err(err) => {
+            Err(PeonyError::Network(format!("Failed to send request to `{}`: {}", url, err)).into())
+        }
     }
 }
 
-pub async fn create_mod_release(
-    config: &ModConfig,
-    mod_files: &ModVersionInfo,
-    changelog: &String,
-    modrinth_url: &ModrinthUrl,
-    version_name: &String,
-    version_type: VersionType
-) -> Result<(), anyhow::Error> {
-    let modrinth_config = config.modrinth.clone();
-    let modrinth_token = match env::var("MODRINTH_TOKEN") {
-        Ok(token) => token,
-        Err(err) => {
-            return Err(anyhow!(
-                "Failed to get Modrinth token from environment: {}",
-                err
-            ))
-        }
-    };
+impl ModrinthPublisher {
+    async fn upload_version(
+        &self,
+        release: &Release,
+        token: &str,
+    ) -> Result<VersionResponse, anyhow::Error> {
+        info!("Uploading `{}` to Modrinth...", release.version_name);
 
-    println!("Uploading to Modrinth...");
+        let form_data = version_request(release, &self.project_id);
 
-    let mut file_part_names = vec![FileType::Mod.part_name()];
+        let mut total_bytes = 0;
+        for file in &release.files {
+            total_bytes += file_size(&file.file_path)?;
+        }
 
-    if mod_files.sources_file.is_some() {
-        file_part_names.push(FileType::Sources.part_name())
-    }
+        let progress_bar = upload_progress_bar(total_bytes, "Uploading to Modrinth".to_owned());
 
-    let mut dependencies: Vec<VersionDependency> = vec![];
+        let form = match create_version_form(release, &form_data, &progress_bar).await {
+            Ok(form) => form,
+            Err(err) => return Err(anyhow!("Failed to create version form: {}", err)),
+        };
 
-    if modrinth_config.dependencies.is_some() {
-        for dep in modrinth_config.dependencies.unwrap() {
-            dependencies.push(VersionDependency::from(dep))
+        let res = match send_request(
+            reqwest::Client::new()
+                .post(format!("{}/version", self.modrinth_url.labrinth))
+                .header("Authorization", token)
+                .multipart(form),
+        )
+        .await
+        {
+            Ok(res) => res,
+            Err(err) => {
+                return Err(PeonyError::Network(format!("Error uploading version: {}", err)).into())
+            }
+        };
+
+        let status = res.status();
+
+        if !status.is_success() {
+            return Err(PeonyError::from_status(
+                status,
+                format!(
+                    "Failed to upload version to Modrinth: {}",
+                    res.text().await.unwrap_or_default()
+                ),
+            ));
+        }
+
+        match res.json::<VersionResponse>().await {
+            Ok(version) => {
+                info!(
+                    "Successfully uploaded `{}` to Modrinth!",
+                    release.version_name
+                );
+                Ok(version)
+            }
+            Err(err) => Err(anyhow!("Failed to parse Modrinth version: {}", err)),
         }
     }
 
-    let form_data = VersionRequest {
-        name: version_name.into(),
-        version_number: mod_files.version.to_owned(),
-        changelog: Some(changelog.to_string()),
-        dependencies,
-        game_versions: config.mc_versions.to_owned(),
-        version_type,
-        loaders: config.loaders.to_owned(),
-        featured: false,
-        requested_status: VersionStatus::Listed,
-        project_id: modrinth_config.project_id,
-        file_parts: file_part_names,
-        primary_file: FileType::Mod.part_name(),
-    };
-
-    let form = match create_mod_form(mod_files, &form_data).await {
-        Ok(form) => form,
-        Err(err) => return Err(anyhow!("Failed to create mod form: {}", err)),
-    };
-
-    let req = match reqwest::Client::new()
-        .post(format!("{}/version", modrinth_url.labrinth))
-        .header("Authorization", &modrinth_token)
-        .multipart(form)
-        .send()
+    async fn delete_version(&self, version_id: &str, token: &str) -> Result<(), anyhow::Error> {
+        info!("Deleting Modrinth version `{}`...", version_id);
+
+        let res = match send_request(
+            reqwest::Client::new()
+                .delete(format!(
+                    "{}/version/{}",
+                    self.modrinth_url.labrinth, version_id
+                ))
+                .header("Authorization", token),
+        )
         .await
-    {
-        Ok(res) => res,
-        Err(err) => return Err(anyhow!("Error uploading mod version: {}", err)),
-    };
-
-    if req.status().is_success() {
-        Ok(println!("Successfully uploaded version to Modrinth!"))
-    } else {
-        Err(anyhow!(
-            "Failed to upload version to Modrinth: {}",
-            req.text().await.unwrap()
-        ))
+        {
+            Ok(res) => res,
+            Err(err) => {
+                return Err(PeonyError::Network(format!(
+                    "Error deleting Modrinth version: {}",
+                    err
+                ))
+                .into())
+            }
+        };
+
+        let status = res.status();
+
+        if status.is_success() {
+            info!("Deleted Modrinth version `{}`!", version_id);
+            Ok(())
+        } else {
+            Err(PeonyError::from_status(
+                status,
+                format!(
+                    "Failed to delete Modrinth version `{}`: {}",
+                    version_id,
+                    res.text().await.unwrap_or_default()
+                ),
+            ))
+        }
     }
 }
 
-pub async fn create_mod_form(
-    mod_files: &ModVersionInfo,
-    request_data: &VersionRequest,
-) -> Result<Form, anyhow::Error> {
-    let mut file_names: Vec<String> = vec![mod_files.mod_file.clone().name];
-
-    let mod_part = JarPart {
-        file_name: mod_files.mod_file.name.clone(),
-        file_part: Part::bytes(mod_files.mod_file.contents.clone())
-            .file_name(mod_files.mod_file.clone().name)
-            .mime_str("application/java-archive")?,
-        file_type: FileType::Mod,
-    };
-
-    let sources_part = match &mod_files.sources_file {
-        Some(file) => {
-            file_names.push(file.name.to_owned());
-
-            let part = Part::bytes(file.contents.clone())
-                .file_name(file.name.clone())
-                .mime_str("application/java-archive")?;
-
-            Some(JarPart {
-                file_name: file.name.clone(),
-                file_part: part,
-                file_type: FileType::Sources,
-            })
+#[async_trait]
+impl Publisher for ModrinthPublisher {
+    fn target(&self) -> Target {
+        Target::Modrinth
+    }
+
+    async fn preflight(&self, version_number: Option<&str>) -> Result<(), anyhow::Error> {
+        let modrinth_token = get_modrinth_token()?;
+
+        let project_url = format!("{}/project/{}", self.modrinth_url.labrinth, self.project_id);
+
+        match get_modrinth_status(&project_url, &modrinth_token).await? {
+            status if status.is_success() => (),
+            StatusCode::UNAUTHORIZED => {
+                return Err(PeonyError::Auth("`MODRINTH_TOKEN` is invalid".to_owned()).into())
+            }
+            StatusCode::NOT_FOUND => {
+                return Err(PeonyError::Config(format!(
+                    "Modrinth project `{}` doesn't exist or `MODRINTH_TOKEN` can't access it",
+                    self.project_id
+                ))
+                .into())
+            }
+            status => {
+                return Err(PeonyError::from_status(
+                    status,
+                    format!("Failed to check Modrinth project: {}", status),
+                ))
+            }
         }
-        None => None,
-    };
 
-    let mut file_parts = vec![mod_part];
+        // Modrinth looks versions up by id or version number here
+        if let Some(version_number) = version_number {
+            match get_modrinth_status(
+                &format!("{}/version/{}", project_url, version_number),
+                &modrinth_token,
+            )
+            .await?
+            {
+                StatusCode::NOT_FOUND => (),
+                status if status.is_success() => {
+                    return Err(PeonyError::Conflict(format!(
+                        "Modrinth version `{}` already exists",
+                        version_number
+                    ))
+                    .into())
+                }
+                status => {
+                    return Err(PeonyError::from_status(
+                        status,
+                        format!("Failed to check Modrinth version: {}", status),
+                    ))
+                }
+            }
+        }
 
-    if let Some(sources_part_val) = sources_part {
-        file_parts.push(sources_part_val);
+        Ok(())
     }
 
-    let form_data = serde_json::to_string(request_data)
-        .map_err(|err| anyhow!("Failed to serialize version request body: {}", err))?;
+    async fn publish(
+        &self,
+        release: &Release,
+        result: &mut PublishResult,
+    ) -> Result<(), anyhow::Error> {
+        let modrinth_token = get_modrinth_token()?;
+
+        for variant_release in release.variant_releases() {
+            // Variants published before a resumed release failed are skipped
+            if let Some(file) = variant_release.primary_file() {
+                if result.has_file(&file.file_name) {
+                    continue;
+                }
+            }
+
+            let version = self
+                .upload_version(&variant_release, &modrinth_token)
+                .await?;
+
+            let url = format!(
+                "{}/project/{}/version/{}",
+                self.modrinth_url.knossos, version.project_id, version.id
+            );
+
+            if result.id.is_none() {
+                result.id = Some(version.id.clone());
+                result.url = Some(url.clone());
+            }
+
+            if !release.variants.is_empty() {
+                result.versions.push(PublishedVersion {
+                    version_name: variant_release.version_name.clone(),
+                    id: version.id,
+                    url: Some(url),
+                    file_names: version
+                        .files
+                        .iter()
+                        .map(|file| file.filename.clone())
+                        .collect(),
+                })
+            }
+
+            result
+                .files
+                .extend(version.files.into_iter().map(|file| PublishedFile {
+                    file_name: file.filename,
+                    url: Some(file.url),
+                    sha512: file.hashes.sha512,
+                }));
+        }
 
-    let mut form = Form::new().text("data", form_data);
+        Ok(())
+    }
+
+    async fn rollback(
+        &self,
+        _release: &Release,
+        result: &mut PublishResult,
+    ) -> Result<(), anyhow::Error> {
+        let version_ids: Vec<String> = if result.versions.is_empty() {
+            result.id.iter().cloned().collect()
+        } else {
+            result
+                .versions
+                .iter()
+                .map(|version| version.id.clone())
+                .collect()
+        };
+
+        if version_ids.is_empty() {
+            return Ok(());
+        }
+
+        let modrinth_token = get_modrinth_token()?;
+
+        // Removed one by one, so a version that fails to be deleted is still
+        // in `result` when resuming
+        for version_id in version_ids {
+            self.delete_version(&version_id, &modrinth_token).await?;
+            result.remove_version(&version_id);
+        }
 
-    for part in file_parts {
-        form = form.part(part.file_type.part_name(), part.file_part);
+        Ok(())
     }
 
-    Ok(form)
+    async fn dry_run(&self, release: &Release) -> Result<Vec<DryRunRequest>, anyhow::Error> {
+        release
+            .variant_releases()
+            .iter()
+            .map(|variant_release| {
+                dry_run_request(
+                    "Modrinth version request",
+                    &version_request(variant_release, &self.project_id),
+                )
+            })
+            .collect()
+    }
 }
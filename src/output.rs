@@ -0,0 +1,74 @@
+use anyhow::anyhow;
+use clap::ValueEnum;
+use std::fs;
+use std::path::Path;
+
+use crate::actions::{is_github_actions, write_outputs, write_step_summary};
+use crate::dry_run::print_dry_run;
+use crate::models::release::ReleaseSummary;
+
+#[derive(Debug, Copy, Clone, ValueEnum)]
+pub enum OutputFormat {
+    Text,
+    Json,
+}
+
+pub fn write_summary(
+    summary: &ReleaseSummary,
+    output_format: OutputFormat,
+    output_file: Option<&Path>,
+) -> Result<(), anyhow::Error> {
+    let summary_json = match serde_json::to_string_pretty(summary) {
+        Ok(json) => json,
+        Err(err) => return Err(anyhow!("Failed to serialize release summary: {}", err)),
+    };
+
+    if let Some(path) = output_file {
+        match fs::write(path, &summary_json) {
+            Ok(_) => (),
+            Err(err) => {
+                return Err(anyhow!(
+                    "Failed to write release summary to `{}`: {}",
+                    path.display(),
+                    err
+                ))
+            }
+        }
+    }
+
+    if is_github_actions() {
+        write_outputs(summary)?;
+
+        if !summary.results.is_empty() {
+            write_step_summary(summary)?
+        }
+    }
+
+    match output_format {
+        // A dry run's requests are part of the JSON summary, so stdout stays
+        // a single JSON document
+        OutputFormat::Json => println!("{}", summary_json),
+        OutputFormat::Text => {
+            // A dry run publishes nothing, so its requests are shown instead
+            if let Some(dry_run) = &summary.dry_run {
+                return print_dry_run(dry_run);
+            }
+
+            if summary.results.is_empty() {
+                return Ok(());
+            }
+
+            println!("Released `{}`!", summary.version_name);
+
+            for result in &summary.results {
+                println!(
+                    "  {}: {}",
+                    result.target.formatted(),
+                    result.url.as_deref().unwrap_or("no url")
+                )
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -3,6 +3,7 @@ use glob::glob;
 use std::fs;
 use std::path::{Path, PathBuf};
 
+use crate::error::PeonyError;
 use crate::{
     models::{
         project_type::modpack::PackFile,
@@ -11,15 +12,21 @@ use crate::{
     util::clean_up,
 };
 
-pub fn get_pack_file() -> Result<PackFile, anyhow::Error> {
-    let file = match fs::read_to_string("pack.toml") {
+pub fn get_pack_file(project_dir: &Path) -> Result<PackFile, anyhow::Error> {
+    let file = match fs::read_to_string(project_dir.join("pack.toml")) {
         Ok(file) => file,
-        Err(err) => return Err(anyhow!("Failed to read pack.toml file: {}", err)),
+        Err(err) => {
+            return Err(
+                PeonyError::Config(format!("Failed to read pack.toml file: {}", err)).into(),
+            )
+        }
     };
 
     let file_parsed: Result<PackFile, anyhow::Error> = match toml::from_str(file.as_str()) {
         Ok(pack) => Ok(pack),
-        Err(err) => Err(anyhow!("Failed to parse pack.toml file: {}", err)),
+        Err(err) => {
+            Err(PeonyError::Config(format!("Failed to parse pack.toml file: {}", err)).into())
+        }
     };
 
     file_parsed
@@ -40,11 +47,22 @@ pub fn get_output_file(tmp_dir_info: &TempInfo) -> Result<OutputFileInfo, anyhow
     let mut glob_pattern = match glob(
         match Path::new(&tmp_dir_info.dir_path).join("*.mrpack").to_str() {
             Some(path) => path,
-            None => return Err(anyhow!("Failed to parse modpack glob to string.")),
+            None => {
+                return Err(PeonyError::Artifact(
+                    "Failed to parse modpack glob to string.".to_owned(),
+                )
+                .into())
+            }
         },
     ) {
         Ok(paths) => paths,
-        Err(err) => return Err(anyhow!("Failed to get paths with glob pattern: {}", err)),
+        Err(err) => {
+            return Err(PeonyError::Artifact(format!(
+                "Failed to get paths with glob pattern: {}",
+                err
+            ))
+            .into())
+        }
     };
 
     let mut mrpack_path_res = None;
@@ -54,16 +72,31 @@ pub fn get_output_file(tmp_dir_info: &TempInfo) -> Result<OutputFileInfo, anyhow
     let file_path = match mrpack_path_res {
         Some(path) => match path {
             Ok(res) => res,
-            Err(err) => return Err(anyhow!("Failed to parse modpack file path: {}", err)),
+            Err(err) => {
+                return Err(PeonyError::Artifact(format!(
+                    "Failed to parse modpack file path: {}",
+                    err
+                ))
+                .into())
+            }
         },
-        None => return Err(anyhow!("Failed to get modpack file path")),
+        None => {
+            return Err(PeonyError::Artifact("Failed to get modpack file path".to_owned()).into())
+        }
     };
     let file_name = match file_path.file_name() {
         Some(os_name) => match os_name.to_str() {
             Some(name) => name.to_string(),
-            None => return Err(anyhow!("Failed to parse file name from OsString to &str")),
+            None => {
+                return Err(PeonyError::Artifact(
+                    "Failed to parse file name from OsString to &str".to_owned(),
+                )
+                .into())
+            }
         },
-        None => return Err(anyhow!("Failed to get mrpack file name")),
+        None => {
+            return Err(PeonyError::Artifact("Failed to get mrpack file name".to_owned()).into())
+        }
     };
 
     Ok(OutputFileInfo {
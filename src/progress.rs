@@ -0,0 +1,58 @@
+use anyhow::anyhow;
+use futures::TryStreamExt;
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressFinish, ProgressStyle};
+use reqwest::Body;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tokio_util::io::ReaderStream;
+
+static PROGRESS_HIDDEN: AtomicBool = AtomicBool::new(false);
+
+// Progress bars are hidden with `--quiet` or JSON logs. They're also hidden
+// when stderr isn't a terminal, like in CI.
+pub fn set_progress_hidden(hidden: bool) {
+    PROGRESS_HIDDEN.store(hidden, Ordering::Relaxed)
+}
+
+pub fn upload_progress_bar(total_bytes: u64, message: String) -> ProgressBar {
+    if PROGRESS_HIDDEN.load(Ordering::Relaxed) {
+        return ProgressBar::hidden();
+    }
+
+    let progress_bar =
+        ProgressBar::with_draw_target(Some(total_bytes), ProgressDrawTarget::stderr())
+            .with_message(message)
+            // Cleared once the upload is done, or failed, so it doesn't linger
+            // above the logs that follow
+            .with_finish(ProgressFinish::AndClear);
+
+    if let Ok(style) = ProgressStyle::with_template(
+        "{msg} [{bar:30}] {bytes}/{total_bytes} ({bytes_per_sec}, {eta})",
+    ) {
+        progress_bar.set_style(style.progress_chars("=> "))
+    }
+
+    progress_bar
+}
+
+pub fn file_size(path: &Path) -> Result<u64, anyhow::Error> {
+    match path.metadata() {
+        Ok(metadata) => Ok(metadata.len()),
+        Err(err) => Err(anyhow!("Failed to read `{}`: {}", path.display(), err)),
+    }
+}
+
+// Streams a file from disk as a request body, advancing the progress bar as
+// chunks are read, so large artifacts are never held in memory.
+pub async fn file_body(path: &Path, progress_bar: &ProgressBar) -> Result<Body, anyhow::Error> {
+    let file = match tokio::fs::File::open(path).await {
+        Ok(file) => file,
+        Err(err) => return Err(anyhow!("Failed to open `{}`: {}", path.display(), err)),
+    };
+
+    let progress_bar = progress_bar.clone();
+    let stream =
+        ReaderStream::new(file).inspect_ok(move |chunk| progress_bar.inc(chunk.len() as u64));
+
+    Ok(Body::wrap_stream(stream))
+}
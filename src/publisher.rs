@@ -0,0 +1,309 @@
+use async_trait::async_trait;
+use std::path::Path;
+use tracing::{error, info, info_span, warn, Instrument};
+
+use crate::actions::print_warning;
+use crate::discord::DiscordPublisher;
+use crate::dry_run::{release_assets, DryRun, DryRunRequest};
+use crate::error::PeonyError;
+use crate::github::GithubPublisher;
+use crate::journal::{finish_journal, save_journal, start_journal};
+use crate::models::journal::ReleaseJournal;
+use crate::models::modrinth::ModrinthUrl;
+use crate::models::release::{PublishResult, PublisherConfig, Release, ReleaseSummary};
+use crate::models::target::{Target, Targets};
+use crate::modrinth::ModrinthPublisher;
+
+#[async_trait]
+pub trait Publisher: Send + Sync {
+    fn target(&self) -> Target;
+
+    // Checks everything that can be checked before anything is built or
+    // published, e.g. that the required tokens are set. The version number
+    // is checked too when it's known and this target hasn't published it.
+    async fn preflight(&self, version_number: Option<&str>) -> Result<(), anyhow::Error>;
+
+    // Fills in `result` as the release progresses, so a failed publish still
+    // reports what was created before it failed.
+    async fn publish(
+        &self,
+        release: &Release,
+        result: &mut PublishResult,
+    ) -> Result<(), anyhow::Error>;
+
+    // Clears what it deleted from `result`, so a target that's only partly
+    // rolled back is resumed from what's left.
+    async fn rollback(
+        &self,
+        release: &Release,
+        result: &mut PublishResult,
+    ) -> Result<(), anyhow::Error>;
+
+    // The requests `publish` would send, without sending them.
+    async fn dry_run(&self, release: &Release) -> Result<Vec<DryRunRequest>, anyhow::Error>;
+}
+
+pub fn create_publishers(
+    targets: &Targets,
+    config: &PublisherConfig,
+) -> Result<Vec<Box<dyn Publisher>>, anyhow::Error> {
+    let mut publishers: Vec<Box<dyn Publisher>> = vec![];
+    let modrinth_url = ModrinthUrl::new(&config.modrinth_staging);
+
+    if targets.contains(Target::Github) {
+        publishers.push(Box::new(GithubPublisher {
+            config: config.github.clone(),
+        }))
+    }
+
+    if targets.contains(Target::Modrinth) {
+        publishers.push(Box::new(ModrinthPublisher {
+            project_id: config.modrinth_project_id.clone(),
+            modrinth_url: modrinth_url.clone(),
+        }))
+    }
+
+    if targets.contains(Target::Discord) {
+        let discord_config = match &config.discord {
+            Some(config) => config,
+            None => {
+                return Err(PeonyError::Config("Failed to get Discord config".to_owned()).into())
+            }
+        };
+
+        publishers.push(Box::new(DiscordPublisher {
+            config: discord_config.clone(),
+            github_config: config.github.clone(),
+            modrinth_project_id: config.modrinth_project_id.clone(),
+            modrinth_url,
+        }))
+    }
+
+    Ok(publishers)
+}
+
+// Runs before the project is built, so a missing token or a taken version
+// fails fast instead of after a long build.
+pub async fn preflight_release(
+    targets: &Targets,
+    config: &PublisherConfig,
+    version_number: Option<&str>,
+    dry_run: bool,
+) -> Result<(), anyhow::Error> {
+    info!("Running preflight checks...");
+
+    let mut passed = true;
+
+    for publisher in create_publishers(targets, config)? {
+        passed &= preflight_publisher(publisher.as_ref(), version_number, dry_run).await?;
+    }
+
+    if passed {
+        info!("Preflight checks passed!");
+    }
+
+    Ok(())
+}
+
+// A dry run doesn't need tokens or network access, so failed checks are only
+// reported there. Returns whether the checks passed.
+async fn preflight_publisher(
+    publisher: &dyn Publisher,
+    version_number: Option<&str>,
+    dry_run: bool,
+) -> Result<bool, anyhow::Error> {
+    match publisher.preflight(version_number).await {
+        Ok(_) => Ok(true),
+        Err(err) if dry_run => {
+            print_warning(&format!(
+                "{} preflight check failed, a real release would fail: {:#}",
+                publisher.target().formatted(),
+                err
+            ));
+            Ok(false)
+        }
+        Err(err) => Err(err),
+    }
+}
+
+pub async fn run_release(
+    release: Release,
+    publisher_config: PublisherConfig,
+    targets: Targets,
+    project_dir: &Path,
+    dry_run: bool,
+) -> Result<ReleaseSummary, anyhow::Error> {
+    let mut journal = ReleaseJournal::new(release, publisher_config, targets);
+
+    let publishers = create_publishers(&journal.targets, &journal.publisher_config)?;
+
+    if dry_run {
+        let dry_run = dry_run_release(&publishers, &journal).await?;
+
+        return Ok(ReleaseSummary::new(&journal.release, vec![]).with_dry_run(dry_run));
+    }
+
+    start_journal(&mut journal, project_dir)?;
+
+    let results = publish_release(&publishers, &mut journal).await?;
+
+    finish_journal(&journal, project_dir)?;
+
+    Ok(ReleaseSummary::new(&journal.release, results))
+}
+
+async fn preflight_publishers(
+    publishers: &[Box<dyn Publisher>],
+    journal: &ReleaseJournal,
+    dry_run: bool,
+) -> Result<(), anyhow::Error> {
+    for publisher in publishers {
+        // A target that already published part of this release would fail
+        // the version check against itself
+        let version_number = match journal.result(publisher.target()) {
+            Some(_) => None,
+            None => Some(journal.release.version_number.as_str()),
+        };
+
+        preflight_publisher(publisher.as_ref(), version_number, dry_run).await?;
+    }
+
+    Ok(())
+}
+
+// Collects the requests every target would send, without publishing anything
+pub async fn dry_run_release(
+    publishers: &[Box<dyn Publisher>],
+    journal: &ReleaseJournal,
+) -> Result<DryRun, anyhow::Error> {
+    preflight_publishers(publishers, journal, true).await?;
+
+    let mut requests = vec![];
+
+    for publisher in publishers {
+        requests.append(&mut publisher.dry_run(&journal.release).await?);
+    }
+
+    Ok(DryRun {
+        assets: release_assets(&journal.release)?,
+        requests,
+    })
+}
+
+// Publishes to every target that hasn't been published to yet, recording
+// each step in the journal so a failed release can be resumed.
+pub async fn publish_release(
+    publishers: &[Box<dyn Publisher>],
+    journal: &mut ReleaseJournal,
+) -> Result<Vec<PublishResult>, anyhow::Error> {
+    let release = journal.release.clone();
+
+    preflight_publishers(publishers, journal, false).await?;
+
+    for publisher in publishers {
+        let mut result = match journal.result(publisher.target()) {
+            Some(result) if result.completed => {
+                info!(
+                    "Already published to {}, skipping...",
+                    publisher.target().formatted()
+                );
+                continue;
+            }
+            Some(result) => result.clone(),
+            None => PublishResult::new(publisher.target()),
+        };
+
+        let publish_res = publisher
+            .publish(&release, &mut result)
+            .instrument(info_span!("publish", target = %publisher.target().formatted()))
+            .await;
+
+        result.completed = publish_res.is_ok();
+        journal.set_result(result);
+        save_journal(journal)?;
+
+        if let Err(err) = publish_res {
+            error!(
+                "Failed to publish to {}: {}",
+                publisher.target().formatted(),
+                err
+            );
+
+            if journal.publisher_config.rollback.unwrap_or(true) {
+                let failed_targets =
+                    roll_back_release(publishers, &release, &mut journal.results).await;
+
+                // Anything that failed to roll back keeps what's still
+                // published, so resuming skips or finishes it
+                journal
+                    .results
+                    .retain(|result| failed_targets.contains(&result.target));
+                save_journal(journal)?;
+            } else {
+                warn!("Rollback is disabled, leaving published releases in place:");
+                for result in &journal.results {
+                    warn!(
+                        "  {}: {}",
+                        result.target.formatted(),
+                        result.url.as_deref().unwrap_or("no url")
+                    )
+                }
+                info!("Continue this release later with `peony resume`");
+            }
+
+            return Err(err.context(format!(
+                "Failed to publish to {}",
+                publisher.target().formatted()
+            )));
+        }
+    }
+
+    Ok(journal.results.clone())
+}
+
+// Rolls back in reverse order, so e.g. the Discord announcement disappears
+// before the releases it links to. Returns the targets that couldn't be
+// rolled back.
+async fn roll_back_release(
+    publishers: &[Box<dyn Publisher>],
+    release: &Release,
+    results: &mut [PublishResult],
+) -> Vec<Target> {
+    info!("Rolling back release `{}`...", release.version_name);
+
+    let mut failed_targets = vec![];
+
+    for result in results.iter_mut().rev() {
+        let publisher = match publishers.iter().find(|p| p.target() == result.target) {
+            Some(publisher) => publisher,
+            None => continue,
+        };
+
+        match publisher.rollback(release, result).await {
+            Ok(_) => info!("  {}: rolled back", result.target.formatted()),
+            Err(err) => {
+                error!(
+                    "  {}: failed to roll back: {}",
+                    result.target.formatted(),
+                    err
+                );
+                failed_targets.push(result.target)
+            }
+        }
+    }
+
+    if failed_targets.is_empty() {
+        info!("Successfully rolled back release!")
+    } else {
+        print_warning(&format!(
+            "Failed to roll back {}, these have to be cleaned up by hand!",
+            failed_targets
+                .iter()
+                .map(|target| target.formatted())
+                .collect::<Vec<String>>()
+                .join(", ")
+        ))
+    }
+
+    failed_targets
+}
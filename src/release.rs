@@ -0,0 +1,350 @@
+use anyhow::anyhow;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tracing::info;
+
+use crate::config::{load_config, MODPACK_CONFIG_FILE, MOD_CONFIG_FILE};
+use crate::error::PeonyError;
+use crate::github::generate_changelog;
+use crate::gradle::{gradlew_name, run_gradle, GradleOptions};
+use crate::journal::{find_journal, finish_journal, verify_artifacts};
+use crate::mc_mod::{get_mod_builds, get_subprojects};
+use crate::models::modrinth::version::VersionType;
+use crate::models::modrinth::Loader;
+use crate::models::project_type::mc_mod::config::ModConfig;
+use crate::models::project_type::mc_mod::version::ModVersionInfo;
+use crate::models::project_type::modpack::{config::ModpackConfig, PackFile};
+use crate::models::release::{PublisherConfig, Release, ReleaseSummary};
+use crate::models::target::Targets;
+use crate::models::util::TempInfo;
+use crate::pack::{get_output_file, get_pack_file, write_pack_file};
+use crate::publisher::{
+    create_publishers, dry_run_release, preflight_release, publish_release, run_release,
+};
+use crate::util::{clean_up, create_temp};
+use crate::version::get_modpack_version_info;
+
+#[derive(Debug, Clone)]
+pub struct ModpackReleaseOptions {
+    pub project_dir: PathBuf,
+    // Defaults to `mrpack.toml` in the project directory
+    pub config: Option<PathBuf>,
+    pub profile: Option<String>,
+    // Overrides the version in `pack.toml`
+    pub version: Option<String>,
+    pub version_type: VersionType,
+    pub targets: Targets,
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct ModReleaseOptions {
+    pub project_dir: PathBuf,
+    // Defaults to `peony_mod.toml` in the project directory
+    pub config: Option<PathBuf>,
+    pub profile: Option<String>,
+    // Replace the config's Gradle tasks if not empty
+    pub gradle_tasks: Vec<String>,
+    // Merged with the config's Gradle properties
+    pub gradle_properties: Vec<(String, String)>,
+    // In seconds
+    pub gradle_timeout: Option<u64>,
+    pub version_type: VersionType,
+    pub targets: Targets,
+    pub dry_run: bool,
+}
+
+pub fn load_modpack_config(
+    options: &ModpackReleaseOptions,
+) -> Result<ModpackConfig, anyhow::Error> {
+    let config_path = options
+        .config
+        .clone()
+        .unwrap_or(options.project_dir.join(MODPACK_CONFIG_FILE));
+    let mut config_file: ModpackConfig = load_config(&config_path)?;
+
+    if let Some(profile) = &options.profile {
+        config_file.apply_profile(profile)?;
+        info!("Using config profile `{}`", profile);
+    }
+
+    Ok(config_file)
+}
+
+pub fn load_mod_config(options: &ModReleaseOptions) -> Result<ModConfig, anyhow::Error> {
+    let config_path = options
+        .config
+        .clone()
+        .unwrap_or(options.project_dir.join(MOD_CONFIG_FILE));
+    let mut config_file: ModConfig = load_config(&config_path)?;
+
+    if let Some(profile) = &options.profile {
+        config_file.apply_profile(profile)?;
+        info!("Using config profile `{}`", profile);
+    }
+
+    Ok(config_file)
+}
+
+// Exports a Packwiz modpack and publishes it to the requested targets
+pub async fn release_modpack(
+    options: ModpackReleaseOptions,
+) -> Result<ReleaseSummary, anyhow::Error> {
+    match which::which("packwiz") {
+        Ok(_) => (),
+        Err(err) => {
+            return Err(
+                PeonyError::Build(format!("Failed to find packwiz executable: {}", err)).into(),
+            )
+        }
+    }
+
+    let config_file = load_modpack_config(&options)?;
+    let pack_file = get_pack_file(&options.project_dir)?;
+
+    let publisher_config = PublisherConfig::from(&config_file);
+
+    preflight_release(
+        &options.targets,
+        &publisher_config,
+        Some(options.version.as_deref().unwrap_or(&pack_file.version)),
+        options.dry_run,
+    )
+    .await?;
+
+    let tmp_info = create_temp(&options.project_dir)?;
+
+    let release_res = build_modpack_release(
+        &options,
+        &config_file,
+        pack_file,
+        publisher_config,
+        &tmp_info,
+    )
+    .await;
+
+    clean_up(&tmp_info.dir_path)?;
+
+    release_res
+}
+
+async fn build_modpack_release(
+    options: &ModpackReleaseOptions,
+    config_file: &ModpackConfig,
+    mut pack_file: PackFile,
+    publisher_config: PublisherConfig,
+    tmp_info: &TempInfo,
+) -> Result<ReleaseSummary, anyhow::Error> {
+    if let Some(ver) = &options.version {
+        let mut new_file_contents = pack_file.clone();
+        new_file_contents.version = ver.clone();
+        let file_contents_string = match toml::to_string(&new_file_contents) {
+            Ok(file) => file,
+            Err(err) => return Err(anyhow!("Failed to parse new pack data to toml: {}", err)),
+        };
+
+        pack_file = new_file_contents;
+
+        write_pack_file(&tmp_info.dir_path, file_contents_string)?
+    }
+
+    // remove previously-exported packs copied from the project, if any
+    remove_mrpack_files(&tmp_info.dir_path);
+
+    info!("Exporting modpack with packwiz...");
+
+    let output = match Command::new("packwiz")
+        .arg("mr")
+        .arg("export")
+        .current_dir(&tmp_info.dir_path)
+        .output()
+    {
+        Ok(output) => output,
+        Err(err) => {
+            return Err(PeonyError::Build(format!("Failed to export with packwiz: {}", err)).into())
+        }
+    };
+
+    if !output.status.success() {
+        return Err(PeonyError::Build(format!(
+            "Failed to export with packwiz ({}): {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+        .into());
+    }
+
+    let output_file_info = get_output_file(tmp_info)?;
+
+    let version_info = get_modpack_version_info(config_file, &pack_file)?;
+
+    let changelog_markdown = generate_changelog(&config_file.github, &options.project_dir).await?;
+
+    let release = Release::from_modpack(
+        &pack_file,
+        &output_file_info,
+        &version_info,
+        &changelog_markdown,
+        options.version_type.clone(),
+    )?;
+
+    run_release(
+        release,
+        publisher_config,
+        options.targets.clone(),
+        &options.project_dir,
+        options.dry_run,
+    )
+    .await
+}
+
+fn remove_mrpack_files(dir: &Path) {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+
+    for entry in entries.flatten() {
+        if entry.path().extension().is_some_and(|ext| ext == "mrpack") {
+            let _ = fs::remove_file(entry.path());
+        }
+    }
+}
+
+// Builds a mod with Gradle and publishes it to the requested targets
+pub async fn release_mod(options: ModReleaseOptions) -> Result<ReleaseSummary, anyhow::Error> {
+    match which::which("java") {
+        Ok(_) => (),
+        Err(err) => {
+            return Err(
+                PeonyError::Build(format!("Failed to find Java executable: {}", err)).into(),
+            )
+        }
+    }
+
+    let gradlew_name = gradlew_name();
+
+    if !options.project_dir.join(gradlew_name).exists() {
+        return Err(PeonyError::Build(format!(
+            "Failed to find gradle script at `{:?}`",
+            options.project_dir.join(gradlew_name)
+        ))
+        .into());
+    }
+
+    let config_file = load_mod_config(&options)?;
+
+    let publisher_config = PublisherConfig::from(&config_file);
+
+    // The version number is read from the built jar, so it can only
+    // be checked right before publishing
+    preflight_release(&options.targets, &publisher_config, None, options.dry_run).await?;
+
+    let tmp_info = match create_temp(&options.project_dir) {
+        Ok(info) => info,
+        Err(err) => return Err(anyhow!("Failed to create temporary directory: {}", err)),
+    };
+
+    let release_res = build_mod_release(&options, &config_file, publisher_config, &tmp_info).await;
+
+    clean_up(&tmp_info.dir_path)?;
+
+    release_res
+}
+
+async fn build_mod_release(
+    options: &ModReleaseOptions,
+    config_file: &ModConfig,
+    publisher_config: PublisherConfig,
+    tmp_info: &TempInfo,
+) -> Result<ReleaseSummary, anyhow::Error> {
+    // remove previously-compiled jars, if any
+    let _ = fs::remove_dir_all(tmp_info.dir_path.join("build").join("libs"));
+
+    for subproject in get_subprojects(&tmp_info.dir_path, config_file) {
+        let _ = fs::remove_dir_all(
+            tmp_info
+                .dir_path
+                .join(subproject.path)
+                .join("build")
+                .join("libs"),
+        );
+    }
+
+    let gradle_options = GradleOptions::new(
+        config_file.gradle.as_ref(),
+        options.gradle_tasks.clone(),
+        options.gradle_properties.clone(),
+        options.gradle_timeout,
+    );
+
+    run_gradle(&tmp_info.dir_path, &gradle_options).await?;
+
+    let builds = get_mod_builds(&tmp_info.dir_path, config_file)?;
+
+    // The GitHub release covers every build, so it's named after all of
+    // their loaders
+    let mut loaders: Vec<Loader> = vec![];
+
+    for build in &builds {
+        for loader in &build.version_info.loaders {
+            if !loaders.contains(loader) {
+                loaders.push(*loader)
+            }
+        }
+    }
+
+    let version_info = ModVersionInfo::new(config_file, &builds[0].mod_info, &loaders)?;
+
+    // Generate changelog from previous GitHub Releases
+    let changelog_markdown = generate_changelog(&config_file.github, &options.project_dir).await?;
+
+    let release = Release::from_mod(
+        config_file,
+        &builds,
+        &version_info,
+        &changelog_markdown,
+        options.version_type.clone(),
+    )?;
+
+    run_release(
+        release,
+        publisher_config,
+        options.targets.clone(),
+        &options.project_dir,
+        options.dry_run,
+    )
+    .await
+}
+
+// Continues an unfinished release from its journal, without rebuilding it
+pub async fn resume_release(
+    project_dir: &Path,
+    version_number: Option<String>,
+    dry_run: bool,
+) -> Result<ReleaseSummary, anyhow::Error> {
+    let mut journal = find_journal(project_dir, version_number)?;
+
+    info!(
+        "Resuming release `{}` from `{}`...",
+        journal.release.version_name,
+        journal.path.display()
+    );
+
+    verify_artifacts(&journal)?;
+
+    let publishers = create_publishers(&journal.targets, &journal.publisher_config)?;
+
+    if dry_run {
+        let dry_run = dry_run_release(&publishers, &journal).await?;
+
+        return Ok(ReleaseSummary::new(&journal.release, vec![]).with_dry_run(dry_run));
+    }
+
+    let results = publish_release(&publishers, &mut journal).await?;
+
+    finish_journal(&journal, project_dir)?;
+
+    Ok(ReleaseSummary::new(&journal.release, results))
+}
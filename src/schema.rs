@@ -0,0 +1,149 @@
+use anyhow::anyhow;
+use clap::ValueEnum;
+use schemars::schema::{InstanceType, Metadata, RootSchema, Schema, SchemaObject};
+use schemars::schema_for;
+use schemars::visit::{visit_schema_object, Visitor};
+use std::collections::BTreeSet;
+
+use crate::models::project_type::mc_mod::config::ModConfig;
+use crate::models::project_type::modpack::config::ModpackConfig;
+
+#[derive(Debug, Copy, Clone, ValueEnum)]
+pub enum SchemaType {
+    Mod,
+    Modpack,
+}
+
+// Keys that aren't part of the config are errors, to catch misspelled keys
+struct StrictConfigVisitor;
+
+impl Visitor for StrictConfigVisitor {
+    fn visit_schema_object(&mut self, schema: &mut SchemaObject) {
+        if let Some(object) = &mut schema.object {
+            if !object.properties.is_empty() && object.additional_properties.is_none() {
+                object.additional_properties = Some(Box::new(Schema::Bool(false)))
+            }
+        }
+
+        visit_schema_object(self, schema)
+    }
+}
+
+// Any config can get its values from the one it `extends`, which is merged in
+// table by table, so no key of a table is required on its own. Arrays are
+// replaced as a whole, so their items keep their required keys. Returns the
+// definitions the table refers to, as they're merged the same way.
+fn clear_merged_required(schema: &mut SchemaObject, references: &mut Vec<String>) {
+    if let Some(reference) = &schema.reference {
+        if let Some(name) = reference.strip_prefix("#/definitions/") {
+            references.push(name.to_owned())
+        }
+    }
+
+    if let Some(subschemas) = &mut schema.subschemas {
+        for subschema in [
+            &mut subschemas.all_of,
+            &mut subschemas.any_of,
+            &mut subschemas.one_of,
+        ]
+        .into_iter()
+        .flatten()
+        .flatten()
+        {
+            if let Schema::Object(subschema) = subschema {
+                clear_merged_required(subschema, references)
+            }
+        }
+    }
+
+    if let Some(object) = &mut schema.object {
+        object.required.clear();
+
+        for property in object
+            .properties
+            .values_mut()
+            .chain(object.additional_properties.as_deref_mut())
+        {
+            if let Schema::Object(property) = property {
+                clear_merged_required(property, references)
+            }
+        }
+    }
+}
+
+pub fn config_schema(schema_type: SchemaType) -> RootSchema {
+    let mut schema = match schema_type {
+        SchemaType::Mod => schema_for!(ModConfig),
+        SchemaType::Modpack => schema_for!(ModpackConfig),
+    };
+
+    // `extends` is resolved before the config is deserialized, so it isn't
+    // part of the config structs
+    schema.schema.object().properties.insert(
+        "extends".to_owned(),
+        SchemaObject {
+            instance_type: Some(InstanceType::String.into()),
+            metadata: Some(Box::new(Metadata {
+                description: Some(
+                    "Path to a config to inherit from, relative to this config".to_owned(),
+                ),
+                ..Default::default()
+            })),
+            ..Default::default()
+        }
+        .into(),
+    );
+
+    let mut references = vec![];
+    let mut cleared = BTreeSet::new();
+
+    clear_merged_required(&mut schema.schema, &mut references);
+
+    while let Some(name) = references.pop() {
+        if !cleared.insert(name.clone()) {
+            continue;
+        }
+
+        if let Some(Schema::Object(definition)) = schema.definitions.get_mut(&name) {
+            clear_merged_required(definition, &mut references)
+        }
+    }
+
+    StrictConfigVisitor.visit_root_schema(&mut schema);
+
+    schema
+}
+
+pub fn print_schema(schema_type: SchemaType) -> Result<(), anyhow::Error> {
+    match serde_json::to_string_pretty(&config_schema(schema_type)) {
+        Ok(schema) => {
+            println!("{}", schema);
+            Ok(())
+        }
+        Err(err) => Err(anyhow!("Failed to serialize config schema: {}", err)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn array_items_keep_required_keys() {
+        let schema = config_schema(SchemaType::Mod);
+
+        let required = |name: &str| match &schema.definitions[name] {
+            Schema::Object(definition) => definition
+                .object
+                .as_ref()
+                .map(|object| object.required.clone())
+                .unwrap_or_default(),
+            Schema::Bool(_) => BTreeSet::new(),
+        };
+
+        assert!(required("ModrinthConfig").is_empty());
+        assert!(required("GithubConfig").is_empty());
+        assert!(required("ModrinthDependency").contains("dependency_type"));
+        assert!(required("SubprojectConfig").contains("path"));
+    }
+}
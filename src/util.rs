@@ -1,17 +1,21 @@
 use crate::models::util::TempInfo;
 use anyhow::anyhow;
+use reqwest::{RequestBuilder, Response};
+use sha2::{Digest, Sha512};
 use std::fs::File;
 use std::path::{Path, PathBuf};
-use std::{env, fs};
+use std::time::Instant;
+use std::{env, fs, io};
+use tracing::{debug, info};
 
-pub fn create_temp() -> Result<TempInfo, anyhow::Error> {
+pub fn create_temp(project_dir: &Path) -> Result<TempInfo, anyhow::Error> {
     let new_uuid = uuid::Uuid::new_v4();
     let new_tmp_dir_name = format!("{}_{}", env!("CARGO_PKG_NAME"), new_uuid);
     let new_tmp_dir = Path::new(env::temp_dir().as_path()).join(&new_tmp_dir_name);
 
-    let current_dir = match env::current_dir() {
+    let project_dir = match project_dir.canonicalize() {
         Ok(dir) => dir,
-        Err(err) => return Err(anyhow!("Failed to get current directory: {}", err)),
+        Err(err) => return Err(anyhow!("Failed to find project directory: {}", err)),
     };
 
     match fs::create_dir(&new_tmp_dir) {
@@ -20,7 +24,7 @@ pub fn create_temp() -> Result<TempInfo, anyhow::Error> {
     }
 
     let copy_files_res: Result<(), anyhow::Error> = match fs_extra::dir::copy(
-        current_dir,
+        project_dir,
         &new_tmp_dir,
         &fs_extra::dir::CopyOptions::new().content_only(true),
     ) {
@@ -35,7 +39,6 @@ pub fn create_temp() -> Result<TempInfo, anyhow::Error> {
 
     match copy_files_res {
         Ok(_) => Ok(TempInfo {
-            dir_name: new_tmp_dir_name.to_owned(),
             dir_path: new_tmp_dir,
         }),
         Err(err) => Err(anyhow!("Failed to get temp directory info: {}", err)),
@@ -43,11 +46,11 @@ pub fn create_temp() -> Result<TempInfo, anyhow::Error> {
 }
 
 pub fn clean_up(tmp_dir: &PathBuf) -> Result<(), anyhow::Error> {
-    println!("Cleaning up...");
+    info!("Cleaning up...");
 
     match fs_extra::dir::remove(tmp_dir) {
         Ok(_) => {
-            println!("Removed temporary directory!");
+            info!("Removed temporary directory!");
             Ok(())
         }
         Err(err) => Err(anyhow!("Failed to remove temporary directory: {}", err)),
@@ -68,13 +71,42 @@ pub fn file_name_from_path(path: &PathBuf) -> Result<String, anyhow::Error> {
     }
 }
 
-pub fn trim_quotes(string: String) -> String {
-    string.trim_matches(&['\"'] as &[_]).to_string()
-}
+pub fn sha512_hash(path: &PathBuf) -> Result<String, anyhow::Error> {
+    let mut file = match File::open(path) {
+        Ok(file) => file,
+        Err(err) => return Err(anyhow!("Failed to read file: {}", err)),
+    };
+
+    let mut hasher = Sha512::new();
 
-pub fn read_file(path: &PathBuf) -> Result<Vec<u8>, anyhow::Error> {
-    match fs::read(path) {
-        Ok(file) => Ok(file),
+    match io::copy(&mut file, &mut hasher) {
+        Ok(_) => Ok(format!("{:x}", hasher.finalize())),
         Err(err) => Err(anyhow!("Failed to read file: {}", err)),
     }
 }
+
+// Sends a request, logging its method, endpoint, status and how long it took
+pub async fn send_request(request: RequestBuilder) -> Result<Response, reqwest::Error> {
+    let (client, request) = request.build_split();
+    let request = request?;
+
+    let method = request.method().clone();
+    let url = request.url().clone();
+    let start = Instant::now();
+
+    let res = client.execute(request).await;
+    let elapsed_ms = start.elapsed().as_millis() as u64;
+
+    match &res {
+        Ok(response) => debug!(
+            %method,
+            %url,
+            status = response.status().as_u16(),
+            elapsed_ms,
+            "HTTP request finished"
+        ),
+        Err(err) => debug!(%method, %url, elapsed_ms, error = %err, "HTTP request failed"),
+    }
+
+    res
+}
@@ -0,0 +1,578 @@
+use glob::Pattern;
+use serde::de::DeserializeOwned;
+use std::path::Path;
+
+use crate::actions::{annotate, is_github_actions};
+use crate::config::{
+    check_config_format_version, interpolate_config, parse_config_document, read_config_document,
+    read_config_string, CONFIG_FORMAT_VERSION, MODPACK_CONFIG_FILE, MOD_CONFIG_FILE,
+};
+use crate::error::PeonyError;
+use crate::models::project_type::mc_mod::config::modrinth::ModrinthDependency;
+use crate::models::project_type::mc_mod::config::ModConfig;
+use crate::models::project_type::modpack::config::ModpackConfig;
+use crate::models::version::VERSION_NAME_PLACEHOLDERS;
+use crate::models::GithubConfig;
+use crate::pack::get_pack_file;
+
+#[derive(Debug, Clone)]
+pub struct ValidationReport {
+    pub file_name: String,
+    pub content: String,
+    pub errors: Vec<ValidationMessage>,
+    pub warnings: Vec<ValidationMessage>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ValidationMessage {
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl ValidationReport {
+    pub fn new(file_name: &str, content: String) -> Self {
+        Self {
+            file_name: file_name.to_owned(),
+            content,
+            errors: vec![],
+            warnings: vec![],
+        }
+    }
+
+    fn message(&self, key_path: &[&str], message: String) -> ValidationMessage {
+        ValidationMessage {
+            line: find_key_line(&self.content, key_path),
+            message,
+        }
+    }
+
+    pub fn error(&mut self, key_path: &[&str], message: String) {
+        let message = self.message(key_path, message);
+        self.errors.push(message)
+    }
+
+    pub fn warning(&mut self, key_path: &[&str], message: String) {
+        let message = self.message(key_path, message);
+        self.warnings.push(message)
+    }
+
+    fn print_message(&self, level: &str, message: &ValidationMessage) {
+        if is_github_actions() {
+            return annotate(level, Some(&self.file_name), message.line, &message.message);
+        }
+
+        match message.line {
+            Some(line) => println!(
+                "{}:{}: {}: {}",
+                self.file_name, line, level, message.message
+            ),
+            None => println!("{}: {}: {}", self.file_name, level, message.message),
+        }
+    }
+
+    pub fn print(&self) {
+        for message in &self.errors {
+            self.print_message("error", message)
+        }
+
+        for message in &self.warnings {
+            self.print_message("warning", message)
+        }
+
+        if self.errors.is_empty() && self.warnings.is_empty() {
+            println!("{}: no problems found", self.file_name)
+        }
+    }
+}
+
+// Problems in the configs are part of the reports, errors are only returned
+// if the configs can't be checked at all
+pub fn validate_project() -> Result<Vec<ValidationReport>, anyhow::Error> {
+    let mut reports = vec![];
+
+    if Path::new(MOD_CONFIG_FILE).exists() {
+        reports.push(validate_mod_config(Path::new(MOD_CONFIG_FILE))?)
+    }
+
+    if Path::new(MODPACK_CONFIG_FILE).exists() {
+        reports.push(validate_modpack_config(Path::new(MODPACK_CONFIG_FILE))?)
+    }
+
+    if reports.is_empty() {
+        return Err(PeonyError::Config(format!(
+            "Failed to find `{}` or `{}` file",
+            MOD_CONFIG_FILE, MODPACK_CONFIG_FILE
+        ))
+        .into());
+    }
+
+    Ok(reports)
+}
+
+pub fn validate_mod_config(path: &Path) -> Result<ValidationReport, anyhow::Error> {
+    let mut report = ValidationReport::new(&path.display().to_string(), read_config_string(path)?);
+
+    let config: ModConfig = match deserialize_config(&mut report, path) {
+        Some(config) => config,
+        None => return Ok(report),
+    };
+
+    if config.loaders.is_empty() {
+        report.error(&["loaders"], "`loaders` must not be empty".to_string())
+    }
+
+    if config.mc_versions.is_empty() {
+        report.error(
+            &["mc_versions"],
+            "`mc_versions` must not be empty".to_string(),
+        )
+    }
+
+    for mc_version in &config.mc_versions {
+        if !is_minecraft_version(mc_version) {
+            report.warning(
+                &["mc_versions"],
+                format!("`{}` doesn't look like a Minecraft version", mc_version),
+            )
+        }
+    }
+
+    check_version_name_format(&mut report, &config.version_name_format);
+    check_github_config(&mut report, &config.github);
+    check_modrinth_project_id(&mut report, &config.modrinth.project_id);
+
+    check_dependencies(
+        &mut report,
+        &["modrinth"],
+        config.modrinth.dependencies.as_deref(),
+    );
+
+    if let Some(artifacts) = &config.artifacts {
+        for (key, glob) in artifacts.globs() {
+            if let Err(err) = Pattern::new(glob) {
+                report.error(
+                    &["artifacts", key],
+                    format!("Invalid glob `{}`: {}", glob, err),
+                )
+            }
+        }
+    }
+
+    if let Some(gradle) = &config.gradle {
+        if gradle.tasks.as_ref().is_some_and(|tasks| tasks.is_empty()) {
+            report.error(
+                &["gradle", "tasks"],
+                "`gradle.tasks` must not be empty".to_string(),
+            )
+        }
+
+        if gradle.timeout == Some(0) {
+            report.error(
+                &["gradle", "timeout"],
+                "`gradle.timeout` must be more than 0 seconds".to_string(),
+            )
+        }
+    }
+
+    let project_dir = project_dir(path);
+
+    if !project_dir.join("gradlew").exists() && !project_dir.join("gradlew.bat").exists() {
+        report.warning(&[], "Failed to find a Gradle wrapper script".to_string())
+    }
+
+    for (index, subproject) in config.subprojects.iter().flatten().enumerate() {
+        let key_path = ["subproject", &index.to_string(), "path"];
+
+        if !project_dir.join(&subproject.path).is_dir() {
+            report.error(
+                &key_path,
+                format!(
+                    "Subproject directory `{}` doesn't exist",
+                    subproject.path.display()
+                ),
+            )
+        }
+
+        if subproject.loaders.as_ref().is_some_and(|l| l.is_empty()) {
+            report.error(
+                &["subproject", &index.to_string(), "loaders"],
+                format!(
+                    "`loaders` of subproject `{}` must not be empty",
+                    subproject.path.display()
+                ),
+            )
+        }
+
+        if subproject
+            .mc_versions
+            .as_ref()
+            .is_some_and(|v| v.is_empty())
+        {
+            report.error(
+                &["subproject", &index.to_string(), "mc_versions"],
+                format!(
+                    "`mc_versions` of subproject `{}` must not be empty",
+                    subproject.path.display()
+                ),
+            )
+        }
+
+        check_dependencies(
+            &mut report,
+            &["subproject", &index.to_string()],
+            subproject.dependencies.as_deref(),
+        );
+    }
+
+    Ok(report)
+}
+
+pub fn validate_modpack_config(path: &Path) -> Result<ValidationReport, anyhow::Error> {
+    let mut report = ValidationReport::new(&path.display().to_string(), read_config_string(path)?);
+
+    let config: ModpackConfig = match deserialize_config(&mut report, path) {
+        Some(config) => config,
+        None => return Ok(report),
+    };
+
+    check_version_name_format(&mut report, &config.version_name_format);
+    check_github_config(&mut report, &config.github);
+    check_modrinth_project_id(&mut report, &config.modrinth.project_id);
+
+    if let Err(err) = get_pack_file(project_dir(path)) {
+        report.error(&[], err.to_string())
+    }
+
+    Ok(report)
+}
+
+fn project_dir(config_path: &Path) -> &Path {
+    config_path.parent().unwrap_or(Path::new("."))
+}
+
+// Deserializes the config, reporting keys that don't exist in it. Missing
+// keys and invalid values are reported by the TOML deserializer itself.
+fn deserialize_config<T: DeserializeOwned>(
+    report: &mut ValidationReport,
+    path: &Path,
+) -> Option<T> {
+    let content = match read_config_document(path) {
+        Ok(document) => document.to_string(),
+        Err(err) => {
+            report.error(&["extends"], err.to_string());
+            return None;
+        }
+    };
+
+    // Line numbers only match the file if nothing was merged into it
+    let is_extended = content != report.content;
+
+    let mut unknown_keys: Vec<Vec<String>> = vec![];
+
+    let deserializer = toml::Deserializer::new(&content);
+
+    let config_res: Result<T, toml::de::Error> =
+        serde_ignored::deserialize(deserializer, |path| unknown_keys.push(path_segments(&path)));
+
+    for key_path in unknown_keys {
+        let key_path: Vec<&str> = key_path.iter().map(|key| key.as_str()).collect();
+        report.error(&key_path, format!("Unknown key `{}`", key_path.join(".")))
+    }
+
+    match config_res {
+        Ok(config) => {
+            check_format_version(report, &content);
+            check_interpolation(report, &content);
+            Some(config)
+        }
+        Err(err) => {
+            let line = match err.span() {
+                Some(span) if !is_extended => Some(content[..span.start].matches('\n').count() + 1),
+                _ => None,
+            };
+
+            report.errors.push(ValidationMessage {
+                line,
+                message: err.message().to_owned(),
+            });
+            None
+        }
+    }
+}
+
+fn check_format_version(report: &mut ValidationReport, content: &str) {
+    let version = match toml::from_str::<toml::Table>(content) {
+        Ok(table) => match table.get("config_format_version") {
+            Some(toml::Value::Integer(version)) => *version as i32,
+            _ => return,
+        },
+        Err(_) => return,
+    };
+
+    if let Err(err) = check_config_format_version(version) {
+        report.error(&["config_format_version"], err.to_string())
+    } else if version < CONFIG_FORMAT_VERSION {
+        report.warning(
+            &["config_format_version"],
+            format!(
+                "Config format version {} is outdated, run `peony migrate-config` to update it \
+                 to version {}",
+                version, CONFIG_FORMAT_VERSION
+            ),
+        )
+    }
+}
+
+fn check_interpolation(report: &mut ValidationReport, content: &str) {
+    let interpolate_res =
+        parse_config_document(content).and_then(|mut document| interpolate_config(&mut document));
+
+    if let Err(err) = interpolate_res {
+        report.error(&[], err.to_string())
+    }
+}
+
+fn path_segments(path: &serde_ignored::Path) -> Vec<String> {
+    match path {
+        serde_ignored::Path::Root => vec![],
+        serde_ignored::Path::Seq { parent, index } => {
+            let mut segments = path_segments(parent);
+            segments.push(index.to_string());
+            segments
+        }
+        serde_ignored::Path::Map { parent, key } => {
+            let mut segments = path_segments(parent);
+            segments.push(key.clone());
+            segments
+        }
+        serde_ignored::Path::Some { parent }
+        | serde_ignored::Path::NewtypeStruct { parent }
+        | serde_ignored::Path::NewtypeVariant { parent } => path_segments(parent),
+    }
+}
+
+// Finds the line a key (or table) is defined on. Only handles the plain
+// `[table]`, `[[array]]` and `key = value` forms, which is all Peony's
+// configs use.
+fn find_key_line(content: &str, key_path: &[&str]) -> Option<usize> {
+    if key_path.is_empty() {
+        return None;
+    }
+
+    let mut table_path: Vec<String> = vec![];
+    let mut array_counts: Vec<(String, usize)> = vec![];
+
+    for (index, line) in content.lines().enumerate() {
+        let line = line.trim();
+
+        let line_path = if let Some(header) = line.strip_prefix("[[") {
+            let header = header.trim_end_matches("]]").trim().to_string();
+
+            let count = match array_counts.iter_mut().find(|(name, _)| *name == header) {
+                Some((_, count)) => {
+                    *count += 1;
+                    *count
+                }
+                None => {
+                    array_counts.push((header.clone(), 0));
+                    0
+                }
+            };
+
+            table_path = split_key(&header);
+            table_path.push(count.to_string());
+            table_path.clone()
+        } else if let Some(header) = line.strip_prefix('[') {
+            table_path = split_key(header.trim_end_matches(']'));
+            table_path.clone()
+        } else if let Some((key, _)) = line.split_once('=') {
+            if line.starts_with('#') {
+                continue;
+            }
+
+            let mut line_path = table_path.clone();
+            line_path.extend(split_key(key));
+            line_path
+        } else {
+            continue;
+        };
+
+        if line_path == key_path {
+            return Some(index + 1);
+        }
+    }
+
+    None
+}
+
+fn split_key(key: &str) -> Vec<String> {
+    key.split('.')
+        .map(|part| part.trim().trim_matches('"').to_string())
+        .collect()
+}
+
+fn check_version_name_format(report: &mut ValidationReport, version_name_format: &str) {
+    // Every other part between `%` signs is a placeholder name
+    let parts: Vec<&str> = version_name_format.split('%').collect();
+
+    if parts.len().is_multiple_of(2) {
+        report.error(
+            &["version_name_format"],
+            "`version_name_format` has an unclosed `%` placeholder".to_string(),
+        );
+        return;
+    }
+
+    for placeholder in parts.iter().skip(1).step_by(2) {
+        if !VERSION_NAME_PLACEHOLDERS.contains(placeholder) {
+            report.error(
+                &["version_name_format"],
+                format!(
+                    "Unknown placeholder `%{}%`, expected one of: {}",
+                    placeholder,
+                    VERSION_NAME_PLACEHOLDERS
+                        .iter()
+                        .map(|p| format!("%{}%", p))
+                        .collect::<Vec<String>>()
+                        .join(", ")
+                ),
+            )
+        }
+    }
+}
+
+fn check_github_config(report: &mut ValidationReport, github_config: &GithubConfig) {
+    if github_config.repo_owner.is_empty() {
+        report.error(
+            &["github", "repo_owner"],
+            "`github.repo_owner` must not be empty".to_string(),
+        )
+    }
+
+    if github_config.repo_name.is_empty() {
+        report.error(
+            &["github", "repo_name"],
+            "`github.repo_name` must not be empty".to_string(),
+        )
+    }
+}
+
+// `table_path` is the table the `dependency` array is in
+fn check_dependencies(
+    report: &mut ValidationReport,
+    table_path: &[&str],
+    dependencies: Option<&[ModrinthDependency]>,
+) {
+    for (index, dependency) in dependencies.iter().copied().flatten().enumerate() {
+        if dependency.project_id.is_none() && dependency.version_id.is_none() {
+            let index_string = index.to_string();
+            let mut key_path = table_path.to_vec();
+            key_path.extend(["dependency", &index_string]);
+
+            report.error(
+                &key_path,
+                format!(
+                    "Dependency {} needs either a `project_id` or a `version_id`",
+                    index + 1
+                ),
+            )
+        }
+    }
+}
+
+fn check_modrinth_project_id(report: &mut ValidationReport, project_id: &str) {
+    if project_id.is_empty() {
+        report.error(
+            &["modrinth", "project_id"],
+            "`modrinth.project_id` must not be empty".to_string(),
+        )
+    }
+}
+
+// Matches releases like `1.20.1`, pre-releases like `1.20-pre1` and
+// snapshots like `23w31a`.
+fn is_minecraft_version(version: &str) -> bool {
+    let chars: Vec<char> = version.chars().collect();
+
+    let is_snapshot = chars.len() == 6
+        && chars[..2].iter().all(|c| c.is_ascii_digit())
+        && chars[2] == 'w'
+        && chars[3..5].iter().all(|c| c.is_ascii_digit())
+        && chars[5].is_ascii_lowercase();
+
+    let release = match version.split_once('-') {
+        Some((release, suffix)) => {
+            if !(suffix.starts_with("pre") || suffix.starts_with("rc")) {
+                return is_snapshot;
+            }
+            release
+        }
+        None => version,
+    };
+
+    let parts: Vec<&str> = release.split('.').collect();
+
+    let is_release = (2..=3).contains(&parts.len())
+        && parts
+            .iter()
+            .all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit()));
+
+    is_release || is_snapshot
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn minecraft_versions() {
+        for version in ["1.20", "1.20.1", "1.20-pre1", "1.20.1-rc1", "23w31a"] {
+            assert!(is_minecraft_version(version), "{}", version);
+        }
+
+        for version in [
+            "",
+            "1",
+            "1.20.",
+            "1.20-beta",
+            "23w31",
+            "23W31a",
+            "1é234",
+            "1.2é",
+        ] {
+            assert!(!is_minecraft_version(version), "{}", version);
+        }
+    }
+
+    #[test]
+    fn key_lines() {
+        let content = r#"config_version = 2
+# loaders = ["fabric"]
+loaders = ["fabric"]
+
+[github]
+repo_owner = "owner"
+
+[modrinth]
+"project_id" = "abc"
+
+[[subproject]]
+path = "fabric"
+
+[[subproject]]
+path = "quilt"
+"#;
+
+        assert_eq!(find_key_line(content, &["config_version"]), Some(1));
+        assert_eq!(find_key_line(content, &["loaders"]), Some(3));
+        assert_eq!(find_key_line(content, &["github"]), Some(5));
+        assert_eq!(find_key_line(content, &["github", "repo_owner"]), Some(6));
+        assert_eq!(find_key_line(content, &["modrinth", "project_id"]), Some(9));
+        assert_eq!(
+            find_key_line(content, &["subproject", "1", "path"]),
+            Some(15)
+        );
+        assert_eq!(find_key_line(content, &["github", "repo_name"]), None);
+        assert_eq!(find_key_line(content, &[]), None);
+    }
+}
@@ -1,14 +1,11 @@
 use crate::models::modrinth::Loader::*;
 use crate::models::project_type::modpack::{config::ModpackConfig, PackFile};
-use crate::models::util::OutputFileInfo;
 use crate::models::version::VersionInfo;
 use anyhow::anyhow;
-use std::fs;
 
 pub fn get_modpack_version_info(
     config_file: &ModpackConfig,
     pack_file: &PackFile,
-    output_info: &OutputFileInfo,
 ) -> Result<VersionInfo, anyhow::Error> {
     let loader_opt = if pack_file.versions.quilt.is_some() {
         Some(Quilt)
@@ -34,14 +31,8 @@ pub fn get_modpack_version_info(
         .replace("%mc_version%", &pack_file.versions.minecraft)
         .replace("%loader%", &loader.formatted());
 
-    let file_contents = match fs::read(output_info.file_path.clone()) {
-        Ok(file) => file,
-        Err(err) => return Err(anyhow!("Failed to read .mrpack file: {}", err)),
-    };
-
     Ok(VersionInfo {
         version_name,
         loader,
-        file_contents,
     })
 }